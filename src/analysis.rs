@@ -0,0 +1,105 @@
+//! Scores every legal move in a position instead of just picking one.
+//!
+//! Useful for reviewing games: each move is played and the resulting position is searched for the opponent, so the
+//! score of every move is as trustworthy as the score of the best one.
+
+use crate::player::minimax::{format_pv, MinimaxPlayer, PvMove, SearchLimits};
+use crate::Othello;
+
+use std::fmt;
+
+/// The search result for one legal move.
+#[derive(Clone, Debug)]
+pub struct MoveAnalysis {
+    pub coords: (usize, usize),
+    /// The expected result for the player making the move under the game's rules (see `eval::outcome`): the final
+    /// disc differential, negated in misère games. Positive is winning.
+    pub score: isize,
+    /// Whether the score was searched to the end of the game.
+    pub exact: bool,
+    /// How many moves deep the search got, counting this move.
+    pub depth: usize,
+    /// The expected line of play, starting with this move.
+    pub pv: Vec<PvMove>,
+    pub nodes: u64,
+}
+
+impl fmt::Display for MoveAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Row: {}, Col: {} score {}{} pv {}",
+            self.coords.0,
+            self.coords.1,
+            self.score,
+            if self.exact { " (exact)" } else { "" },
+            format_pv(&self.pv)
+        )
+    }
+}
+
+/// Analyzes every legal move of the active player with plain minimax players.
+///
+/// Returns the moves sorted from best to worst. Moves with equal scores keep the order `successors` gives them.
+///
+/// # Examples
+///
+/// ```
+/// # use othlib::analysis::analyze;
+/// # use othlib::player::minimax::{MinimaxPlayer, SearchLimits};
+/// # use othlib::Othello;
+/// let player_one = MinimaxPlayer::new('X');
+/// let player_two = MinimaxPlayer::new('O');
+/// let game = Othello::with_players(&player_one, &player_two, 4, 4);
+///
+/// let moves = analyze(&game, SearchLimits::default());
+/// assert_eq!(4, moves.len());
+/// assert!(moves.windows(2).all(|pair| pair[0].score >= pair[1].score));
+/// assert_eq!(Some(moves[0].coords), player_one.search(&game).best_move);
+/// ```
+pub fn analyze(game: &Othello, limits: SearchLimits) -> Vec<MoveAnalysis> {
+    analyze_with(game, limits, MinimaxPlayer::new)
+}
+
+/// Analyzes every legal move of the active player, using `make_player` to create the searching player.
+///
+/// `make_player` is given the symbol to search for, which is the opponent of the player being analyzed. `limits`
+/// replace whatever limits the player was created with: the depth is reduced by the move being analyzed, and the time
+/// is shared evenly between the moves.
+pub fn analyze_with<F>(game: &Othello, limits: SearchLimits, make_player: F) -> Vec<MoveAnalysis>
+where
+    F: Fn(char) -> MinimaxPlayer,
+{
+    let symbol = game.get_active_symbol();
+    let opponent_symbol = game.symbol_from_player(!game.active_player());
+    let successors = game.successors(symbol);
+    let child_limits = SearchLimits {
+        depth: limits.depth.map(|depth| depth.saturating_sub(1)),
+        time: limits
+            .time
+            .map(|time| time / successors.len().max(1) as u32),
+    };
+    let opponent = make_player(opponent_symbol).with_limits(child_limits);
+
+    let mut analysis = successors
+        .into_iter()
+        .map(|(row, col)| {
+            let mut child = game.clone();
+            child.play_move(row, col, symbol);
+            let info = opponent.search(&child);
+            let mut pv = info.pv;
+            pv.insert(0, Some((row, col)));
+            MoveAnalysis {
+                coords: (row, col),
+                score: -info.score,
+                exact: info.exact,
+                depth: info.depth + 1,
+                pv,
+                nodes: info.nodes,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    analysis.sort_by_key(|analysis| std::cmp::Reverse(analysis.score));
+    analysis
+}
@@ -1,16 +1,22 @@
 //! Contains the game board. This does _not_ include logic about how a game is played.
 //!
 //! The board has no notions of players or even specific restrictions on count.
-//! It only keeps an NxM grid of `Option<char>`s. This entire API is safe.
+//! It only keeps an NxM grid of `Option<char>`s and a `Mask` of the cells that aren't part of the board. This entire
+//! API is safe.
+
+use crate::rng::Rng;
+use crate::rules::Grid;
 
 use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub struct Board {
     rows: usize,
     cols: usize,
     grid: Vec<Vec<Option<char>>>,
+    mask: Mask,
 }
 
 impl Board {
@@ -23,7 +29,16 @@ impl Board {
     }
 
     pub fn with_state(rows: usize, cols: usize, grid: Vec<Vec<Option<char>>>) -> Self {
-        Self { rows, cols, grid }
+        Self {
+            rows,
+            cols,
+            grid,
+            mask: Mask::full(rows, cols),
+        }
+    }
+
+    pub fn mask(&self) -> &Mask {
+        &self.mask
     }
 }
 
@@ -33,7 +48,40 @@ impl Board {
             rows,
             cols,
             grid: vec![vec![None; cols]; rows],
+            mask: Mask::full(rows, cols),
+        }
+    }
+
+    /// Creates an empty board with the shape of `mask`.
+    pub fn with_mask(mask: Mask) -> Self {
+        Self {
+            rows: mask.rows,
+            cols: mask.cols,
+            grid: vec![vec![None; mask.cols]; mask.rows],
+            mask,
+        }
+    }
+
+    /// Blocks the cells `mask` blocks. Fails if the sizes differ or a disc is on a cell that would be blocked.
+    pub fn apply_mask(&mut self, mask: Mask) -> Result<(), String> {
+        if (mask.rows, mask.cols) != (self.rows, self.cols) {
+            return Err(format!(
+                "The shape is for a {}x{} board, not {}x{}.",
+                mask.rows, mask.cols, self.rows, self.cols
+            ));
+        }
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                if mask.is_blocked(row, col) && !self.is_cell_empty(row, col) {
+                    return Err(format!(
+                        "The shape blocks Row {}, Col {}, which has a disc on it.",
+                        row, col
+                    ));
+                }
+            }
         }
+        self.mask = mask;
+        Ok(())
     }
 
     pub fn get_cell(&self, row: usize, col: usize) -> Option<char> {
@@ -48,8 +96,18 @@ impl Board {
         self.grid[row][col].is_none()
     }
 
+    /// Whether a cell is part of the board: inside the grid and not blocked.
     pub fn is_in_bounds(&self, row: usize, col: usize) -> bool {
-        row < self.rows && col < self.cols
+        row < self.rows && col < self.cols && !self.mask.is_blocked(row, col)
+    }
+
+    pub fn is_blocked(&self, row: usize, col: usize) -> bool {
+        self.mask.is_blocked(row, col)
+    }
+
+    /// Counts the cells that could still be played on.
+    pub fn empties(&self) -> usize {
+        self.mask.open_cells() - self.char_counts().values().sum::<usize>()
     }
 
     pub fn char_counts(&self) -> HashMap<char, usize> {
@@ -69,3 +127,255 @@ impl fmt::Display for Board {
         write!(f, "{}x{}", self.rows(), self.cols())
     }
 }
+
+impl Board {
+    /// Encodes the grid as text, one chunk of cells per row separated by `/`, starting at row 0.
+    /// Empty cells are written as `.` and blocked cells as `#`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use othlib::board::Board;
+    /// let mut board = Board::with_size(2, 3);
+    /// board.set_cell(0, 1, 'X');
+    /// board.set_cell(1, 2, 'O');
+    /// assert_eq!(".X./..O", board.encode());
+    /// ```
+    pub fn encode(&self) -> String {
+        (0..self.rows)
+            .map(|row| {
+                (0..self.cols)
+                    .map(|col| match self.grid[row][col] {
+                        _ if self.is_blocked(row, col) => BLOCKED,
+                        Some(symbol) => symbol,
+                        None => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Rebuilds a board from the output of `encode`. Returns `None` if the rows aren't all the same length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use othlib::board::Board;
+    /// let board = Board::decode(".X./#.O").unwrap();
+    /// assert_eq!((2, 3), (board.rows(), board.cols()));
+    /// assert_eq!(Some('O'), board.get_cell(1, 2));
+    /// assert!(board.is_blocked(1, 0) && !board.is_in_bounds(1, 0));
+    /// assert!(Board::decode("..X/.").is_none());
+    /// ```
+    pub fn decode(encoded: &str) -> Option<Self> {
+        let grid = encoded
+            .split('/')
+            .map(|row| {
+                row.chars()
+                    .map(|cell| match cell {
+                        '.' | BLOCKED => None,
+                        _ => Some(cell),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let cols = grid[0].len();
+        if cols == 0 || grid.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+
+        let mut board = Self::with_state(grid.len(), cols, grid);
+        for (row, line) in encoded.split('/').enumerate() {
+            for (col, cell) in line.chars().enumerate() {
+                if cell == BLOCKED {
+                    board.mask.block(row, col);
+                }
+            }
+        }
+        Some(board)
+    }
+}
+
+/// How blocked cells are written in board and mask text.
+pub const BLOCKED: char = '#';
+
+/// # The shape of a board
+///
+/// Marks the cells of the grid that aren't part of the board. Blocked cells can't be played on, and a line of discs
+/// stops at them the same way it stops at the edge.
+///
+/// Masks are written like boards, one row at a time from row 0 with `/` (or new lines) between rows, `.` for open
+/// cells and `#` for blocked ones.
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct Mask {
+    rows: usize,
+    cols: usize,
+    blocked: Vec<Vec<bool>>,
+}
+
+impl Mask {
+    /// The whole rectangle.
+    pub fn full(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            blocked: vec![vec![false; cols]; rows],
+        }
+    }
+
+    /// # Cuts the corners off the rectangle
+    ///
+    /// Each corner loses a triangle a quarter of the shorter side across.
+    ///
+    /// ```
+    /// # use othlib::board::Mask;
+    /// assert_eq!("##....##/#......#/......../......../......../......../#......#/##....##",
+    ///            Mask::octagon(8, 8).to_string());
+    /// ```
+    pub fn octagon(rows: usize, cols: usize) -> Self {
+        let cut = rows.min(cols) / 4;
+        let mut mask = Self::full(rows, cols);
+        for row in 0..rows {
+            for col in 0..cols {
+                let (down, across) = (row.min(rows - 1 - row), col.min(cols - 1 - col));
+                if down + across < cut {
+                    mask.block(row, col);
+                }
+            }
+        }
+        mask
+    }
+
+    /// # Cuts a hexagon out of a square grid
+    ///
+    /// For hex boards, whose cells are stored by axial coordinates: each row is a line of hexes, and each step up a
+    /// row shifts half a cell to the right. A hexagon `size` cells across leaves out the triangles in the bottom left
+    /// and top right corners.
+    ///
+    /// ```
+    /// # use othlib::board::Mask;
+    /// assert_eq!("##.../#..../...../....#/...##", Mask::hexagon(5).to_string());
+    /// ```
+    pub fn hexagon(size: usize) -> Self {
+        let side = size / 2;
+        let mut mask = Self::full(size, size);
+        for row in 0..size {
+            for col in 0..size {
+                if row + col < side || row + col > side + size - 1 {
+                    mask.block(row, col);
+                }
+            }
+        }
+        mask
+    }
+
+    /// # Blocks `count` random cells
+    ///
+    /// The four starting cells in the middle are never blocked. Asking for more obstacles than there are other cells
+    /// blocks all of them.
+    ///
+    /// ```
+    /// # use othlib::board::Mask;
+    /// let mask = Mask::obstacles(8, 8, 6, 1);
+    /// assert_eq!(58, mask.open_cells());
+    /// assert!(!mask.is_blocked(3, 3) && !mask.is_blocked(4, 4));
+    /// assert_eq!(mask, Mask::obstacles(8, 8, 6, 1));
+    /// ```
+    pub fn obstacles(rows: usize, cols: usize, count: usize, seed: u64) -> Self {
+        let mut mask = Self::full(rows, cols);
+        let start = Grid::Square.middle(rows, cols);
+        let mut cells = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .filter(|cell| !start.contains(cell))
+            .collect::<Vec<_>>();
+        let mut rng = Rng::with_seed(seed);
+        for _ in 0..count.min(cells.len()) {
+            let (row, col) = cells.swap_remove(rng.below(cells.len()));
+            mask.block(row, col);
+        }
+        mask
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Whether a cell is blocked. Cells outside the grid aren't.
+    pub fn is_blocked(&self, row: usize, col: usize) -> bool {
+        row < self.rows && col < self.cols && self.blocked[row][col]
+    }
+
+    pub fn block(&mut self, row: usize, col: usize) {
+        self.blocked[row][col] = true;
+    }
+
+    /// Counts the cells that aren't blocked.
+    pub fn open_cells(&self) -> usize {
+        self.blocked
+            .iter()
+            .flatten()
+            .filter(|&&blocked| !blocked)
+            .count()
+    }
+}
+
+impl FromStr for Mask {
+    type Err = String;
+
+    /// # Parses a drawn mask
+    ///
+    /// ```
+    /// # use othlib::board::Mask;
+    /// let mask = "#..#\n....\n....\n#..#".parse::<Mask>().unwrap();
+    /// assert_eq!("#..#/..../..../#..#", mask.to_string());
+    /// assert!("#..#/...".parse::<Mask>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s
+            .split(['/', '\n'])
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+        let cols = lines.first().map_or(0, |line| line.chars().count());
+        if cols == 0 || lines.iter().any(|line| line.chars().count() != cols) {
+            return Err("Every row of a shape needs the same number of cells.".to_string());
+        }
+
+        let mut mask = Self::full(lines.len(), cols);
+        for (row, line) in lines.iter().enumerate() {
+            for (col, cell) in line.chars().enumerate() {
+                match cell {
+                    '.' => {}
+                    BLOCKED => mask.block(row, col),
+                    other => {
+                        return Err(format!(
+                            "Shapes are drawn with `.` and `{}`, not `{}`.",
+                            BLOCKED, other
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(mask)
+    }
+}
+
+impl fmt::Display for Mask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows = self
+            .blocked
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&blocked| if blocked { BLOCKED } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", rows.join("/"))
+    }
+}
@@ -0,0 +1,232 @@
+//! A small command line parser for the `othello` binary.
+//!
+//! Each subcommand describes its options with `Opt`s. Parsing sorts the arguments into option values, flags and
+//! positional arguments, and `Command::help` writes the `--help` text from the same descriptions so the two can't
+//! drift apart. Options take their value as the next argument or after `=` (`--rows 6` or `--rows=6`), and `--`
+//! ends option parsing.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// A command line option.
+#[derive(Copy, Clone, Debug)]
+pub struct Opt {
+    pub long: &'static str,
+    pub short: Option<char>,
+    /// The name of the option's value in the help text, or `None` for flags.
+    pub value: Option<&'static str>,
+    pub help: &'static str,
+}
+
+impl Opt {
+    /// An option that takes a value.
+    pub const fn value(long: &'static str, value: &'static str, help: &'static str) -> Self {
+        Self {
+            long,
+            short: None,
+            value: Some(value),
+            help,
+        }
+    }
+
+    /// An option without a value.
+    pub const fn flag(long: &'static str, help: &'static str) -> Self {
+        Self {
+            long,
+            short: None,
+            value: None,
+            help,
+        }
+    }
+
+    pub const fn short(mut self, short: char) -> Self {
+        self.short = Some(short);
+        self
+    }
+}
+
+/// The help option every command understands.
+pub const HELP: Opt = Opt::flag("help", "Print this help").short('h');
+
+/// A subcommand and the options it accepts.
+#[derive(Copy, Clone, Debug)]
+pub struct Command {
+    pub name: &'static str,
+    /// The arguments after the command name, like `[OPTIONS] <BOARD> <SIDE>`.
+    pub usage: &'static str,
+    pub about: &'static str,
+    pub options: &'static [Opt],
+}
+
+impl Command {
+    /// Writes the `--help` text.
+    pub fn help(&self) -> String {
+        let mut help = format!(
+            "{}\n\nUsage: othello {} {}\n\nOptions:\n",
+            self.about, self.name, self.usage
+        );
+        let options = self.options.iter().chain(std::iter::once(&HELP));
+        let labels = options
+            .clone()
+            .map(|opt| {
+                let short = opt
+                    .short
+                    .map_or("    ".to_string(), |x| format!("-{}, ", x));
+                let value = opt.value.map_or(String::new(), |x| format!(" <{}>", x));
+                format!("{}--{}{}", short, opt.long, value)
+            })
+            .collect::<Vec<_>>();
+        let width = labels.iter().map(String::len).max().unwrap_or(0);
+        for (label, opt) in labels.iter().zip(options) {
+            writeln!(help, "  {:width$}  {}", label, opt.help, width = width)
+                .expect("Writing to a string can't fail");
+        }
+        help
+    }
+
+    /// # Sorts `args` into options, flags and positional arguments
+    ///
+    /// ```
+    /// # use othlib::cli::{Command, Opt};
+    /// const OPTIONS: &[Opt] = &[Opt::value("rows", "N", "Board rows"), Opt::flag("think", "Show search info")];
+    /// let command = Command { name: "play", usage: "[OPTIONS] [PLAYERS]", about: "Plays", options: OPTIONS };
+    /// let args = ["--rows=6", "human", "--think", "minimax"].map(String::from);
+    /// let matches = command.parse(&args).unwrap();
+    /// assert_eq!(Some(6), matches.get::<usize>("rows").unwrap());
+    /// assert!(matches.flag("think"));
+    /// assert_eq!(["human", "minimax"], matches.positionals());
+    /// assert!(command.parse(&["--cols".to_string()]).is_err());
+    /// ```
+    pub fn parse(&self, args: &[String]) -> Result<Matches, String> {
+        let mut matches = Matches::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                matches.positionals.extend(args.cloned());
+                break;
+            }
+
+            let (opt, inline) = if let Some(long) = arg.strip_prefix("--") {
+                let mut parts = long.splitn(2, '=');
+                let name = parts.next().unwrap_or("");
+                (self.find(|opt| opt.long == name, arg)?, parts.next())
+            } else if arg.len() == 2 && arg.starts_with('-') && arg != "-" {
+                let short = arg.chars().nth(1);
+                (self.find(|opt| opt.short == short, arg)?, None)
+            } else {
+                matches.positionals.push(arg.clone());
+                continue;
+            };
+
+            match (opt.value, inline) {
+                (None, None) => {
+                    matches.flags.push(opt.long);
+                }
+                (None, Some(_)) => return Err(format!("`--{}` doesn't take a value", opt.long)),
+                (Some(_), Some(value)) => {
+                    matches.values.insert(opt.long, value.to_string());
+                }
+                (Some(name), None) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("`--{}` needs a value: <{}>", opt.long, name))?;
+                    matches.values.insert(opt.long, value.clone());
+                }
+            }
+        }
+        Ok(matches)
+    }
+
+    fn find<F: Fn(&Opt) -> bool>(&self, matches: F, arg: &str) -> Result<Opt, String> {
+        self.options
+            .iter()
+            .chain(std::iter::once(&HELP))
+            .find(|opt| matches(opt))
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "Unknown option `{}`, see `othello {} --help`",
+                    arg, self.name
+                )
+            })
+    }
+}
+
+/// The arguments of a command, sorted by `Command::parse`.
+#[derive(Clone, Debug, Default)]
+pub struct Matches {
+    values: HashMap<&'static str, String>,
+    flags: Vec<&'static str>,
+    positionals: Vec<String>,
+}
+
+impl Matches {
+    pub fn flag(&self, long: &str) -> bool {
+        self.flags.contains(&long)
+    }
+
+    pub fn value(&self, long: &str) -> Option<&str> {
+        self.values.get(long).map(String::as_str)
+    }
+
+    /// Parses the value of an option, failing with a message naming the option if it doesn't parse.
+    pub fn get<T: FromStr>(&self, long: &str) -> Result<Option<T>, String> {
+        self.value(long)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("`{}` isn't a valid value for `--{}`", value, long))
+            })
+            .transpose()
+    }
+
+    pub fn positionals(&self) -> &[String] {
+        &self.positionals
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const OPTIONS: &[Opt] = &[
+        Opt::value("rows", "N", "Board rows").short('r'),
+        Opt::flag("plain", "Use prompts"),
+    ];
+    const COMMAND: Command = Command {
+        name: "play",
+        usage: "[OPTIONS]",
+        about: "Plays a game",
+        options: OPTIONS,
+    };
+
+    fn parse(args: &[&str]) -> Result<Matches, String> {
+        COMMAND.parse(&args.iter().map(|x| x.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn reports_bad_arguments() {
+        assert!(parse(&["--columns", "4"])
+            .unwrap_err()
+            .contains("--columns"));
+        assert!(parse(&["--plain=yes"]).is_err());
+        assert!(parse(&["-r", "four"])
+            .unwrap()
+            .get::<usize>("rows")
+            .unwrap_err()
+            .contains("four"));
+        let matches = parse(&["-h", "--", "--plain"]).unwrap();
+        assert!(matches.flag("help") && !matches.flag("plain"));
+        assert_eq!(["--plain"], matches.positionals());
+    }
+
+    #[test]
+    fn help_lists_every_option() {
+        let help = COMMAND.help();
+        assert!(help.contains("Usage: othello play [OPTIONS]"));
+        assert!(help.contains("-r, --rows <N>"));
+        assert!(help.contains("--plain"));
+        assert!(help.contains("-h, --help"));
+    }
+}
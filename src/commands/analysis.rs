@@ -0,0 +1,111 @@
+//! Scoring and solving positions.
+
+use super::{
+    captures, grid, option, parse, position, setup, topology, variant, CAPTURES, GRID, SETUP, TIME,
+    TOPOLOGY, VARIANT,
+};
+use othlib::cli::{Command, Opt};
+use othlib::player::minimax::{format_pv, MinimaxPlayer, SearchLimits};
+use othlib::{ActivePlayer, Othello};
+
+use std::time::Duration;
+
+pub const ANALYZE: Command = Command {
+    name: "analyze",
+    usage: "[OPTIONS] <BOARD> <SIDE>",
+    about: "Scores every legal move for the side to move, best first.\n\nBoards are rows of cells from row 0 \
+            up, split by `/`, e.g. ..../.XO./.OX./.... The side is X or O.",
+    options: &[
+        Opt::value("depth", "N", "How many moves ahead to search").short('d'),
+        TIME,
+        VARIANT,
+        TOPOLOGY,
+        SETUP,
+        GRID,
+        CAPTURES,
+    ],
+};
+
+pub const SOLVE: Command = Command {
+    name: "solve",
+    usage: "[OPTIONS] <BOARD> <SIDE>",
+    about: "Searches a position to the end of the game. Prints the best move, the final disc differential with best \
+            play, and the expected line.",
+    options: &[TIME, VARIANT, TOPOLOGY, SETUP, GRID, CAPTURES],
+};
+
+/// `othello analyze`
+pub fn analyze(args: &[String]) {
+    let matches = parse(&ANALYZE, args, 2..=2);
+    let (board, active) = position(&matches.positionals()[0], &matches.positionals()[1]);
+    let limits = SearchLimits {
+        depth: option(matches.get::<usize>("depth")),
+        time: option(matches.get::<u64>("time")).map(Duration::from_millis),
+    };
+
+    let (player_one, player_two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    let game = Othello::with_board(&player_one, &player_two, board, active)
+        .with_variant(variant(&matches))
+        .with_topology(topology(&matches))
+        .with_setup(setup(&matches))
+        .with_grid(grid(&matches))
+        .with_captures(captures(&matches));
+    println!("{}", game);
+
+    let moves = othlib::analysis::analyze(&game, limits);
+    if moves.is_empty() {
+        println!("Player {} has no legal moves.", game.active_as_num());
+    }
+    for analysis in moves {
+        println!("{}", analysis);
+    }
+}
+
+/// `othello solve`
+pub fn solve(args: &[String]) {
+    let matches = parse(&SOLVE, args, 2..=2);
+    let (board, active) = position(&matches.positionals()[0], &matches.positionals()[1]);
+    let limits = SearchLimits {
+        depth: None,
+        time: option(matches.get::<u64>("time")).map(Duration::from_millis),
+    };
+
+    let (player_one, player_two) = (
+        MinimaxPlayer::new('X').with_limits(limits),
+        MinimaxPlayer::new('O').with_limits(limits),
+    );
+    let game = Othello::with_board(&player_one, &player_two, board, active)
+        .with_variant(variant(&matches))
+        .with_topology(topology(&matches))
+        .with_setup(setup(&matches))
+        .with_grid(grid(&matches))
+        .with_captures(captures(&matches));
+    println!("{}", game);
+
+    let symbol = game.get_active_symbol();
+    let info = match active {
+        ActivePlayer::PlayerOne => player_one.search(&game),
+        ActivePlayer::PlayerTwo => player_two.search(&game),
+    };
+    match info.best_move {
+        Some((row, col)) => println!("Best move for {}: Row: {}, Col: {}", symbol, row, col),
+        None if game.has_more_moves() => println!("{} has to pass.", symbol),
+        None => println!("The game is over."),
+    }
+    // Searches score the variant's outcome, which is the differential negated in misère
+    let differential = game.variant().outcome(info.score);
+    if info.exact {
+        println!("Final disc differential for {}: {}", symbol, differential);
+    } else {
+        println!(
+            "Ran out of time after {} moves, estimated disc differential for {}: {}",
+            info.depth, symbol, differential
+        );
+    }
+    println!("Line: {}", format_pv(&info.pv));
+    println!(
+        "Searched {} positions in {:.3}s",
+        info.nodes,
+        info.elapsed.as_secs_f64()
+    );
+}
@@ -0,0 +1,375 @@
+//! Matches between bots: self-play data, tournaments, SPRT tests and the opening sets they start from.
+
+use super::{
+    board_size, fail, option, parse, player_spec, rules, BALANCE, BOOK, CAPTURES, COLS, GAMES,
+    GRID, LAYOUT, LEVEL, OPENING_MOVES, ROWS, SEED, SETUP, SHAPE, SIZE, SWAP, THREADS, TIME,
+    TOPOLOGY, VARIANT,
+};
+use othlib::cli::{Command, Matches, Opt};
+use othlib::openings::{Generator, Opening, OpeningSet};
+use othlib::player::spec::PlayerSpec;
+use othlib::rules::Rules;
+use othlib::selfplay::SelfPlay;
+use othlib::sprt::{Match, Sprt, Verdict};
+use othlib::tournament::Tournament;
+
+use std::fs::File;
+use std::io::BufWriter;
+
+pub const SELFPLAY: Command = Command {
+    name: "selfplay",
+    usage: "[OPTIONS] <PLAYER> <PLAYER> <OUT.csv>",
+    about: "Lets two bots play each other and writes every position to a CSV file for training.",
+    options: &[
+        GAMES,
+        ROWS,
+        COLS,
+        SIZE,
+        LEVEL,
+        TIME,
+        SEED,
+        Opt::value(
+            "random-moves",
+            "N",
+            "Random moves at the start of each game (default 4)",
+        ),
+        Opt::value(
+            "solve-empties",
+            "N",
+            "Solve positions with this many empty cells or fewer (default 8)",
+        ),
+        BOOK,
+        BALANCE,
+        VARIANT,
+        TOPOLOGY,
+        SETUP,
+        LAYOUT,
+        GRID,
+        CAPTURES,
+        SHAPE,
+        SWAP,
+        THREADS,
+    ],
+};
+
+pub const TOURNAMENT: Command = Command {
+    name: "tournament",
+    usage: "[OPTIONS] <PLAYER> <PLAYER> [PLAYER...]",
+    about: "Plays a round robin between bots and prints the standings, a crosstable and Elo ratings.\n\nEvery pair \
+            of players plays each opening twice, once with each player moving first.",
+    options: &[
+        ROWS,
+        COLS,
+        SIZE,
+        LEVEL,
+        TIME,
+        SEED,
+        Opt::value("openings", "N", "How many random openings to play (default 4, 0 for none)"),
+        OPENING_MOVES,
+        BALANCE,
+        BOOK,
+        VARIANT,
+        TOPOLOGY,
+        SETUP,
+        LAYOUT,
+        GRID,
+        CAPTURES,
+        SHAPE,
+        SWAP,
+        THREADS,
+        Opt::value("csv", "FILE", "Also write the standings and crosstable to a CSV file"),
+    ],
+};
+
+pub const SPRT: Command = Command {
+    name: "sprt",
+    usage: "[OPTIONS] <NEW> <BASELINE>",
+    about: "Tests whether a player is stronger than a baseline, stopping once the result is clear.\n\nPlays pairs of \
+            games from the same opening with colors swapped and runs a sequential probability ratio test: H0 is that \
+            the new player is elo0 stronger, H1 that it's elo1 stronger.",
+    options: &[
+        Opt::value("elo0", "ELO", "The Elo difference of H0 (default 0)"),
+        Opt::value("elo1", "ELO", "The Elo difference of H1 (default 10)"),
+        Opt::value("alpha", "P", "The chance of accepting H1 when H0 is true (default 0.05)"),
+        Opt::value("beta", "P", "The chance of accepting H0 when H1 is true (default 0.05)"),
+        Opt::value("max-games", "N", "Stop without a result after this many games"),
+        ROWS,
+        COLS,
+        SIZE,
+        LEVEL,
+        TIME,
+        SEED,
+        OPENING_MOVES,
+        BALANCE,
+        BOOK,
+        VARIANT,
+        TOPOLOGY,
+        SETUP,
+        LAYOUT,
+        GRID,
+        CAPTURES,
+        SHAPE,
+        SWAP,
+        THREADS,
+    ],
+};
+
+pub const OPENINGS: Command = Command {
+    name: "openings",
+    usage: "[OPTIONS] <OUT>",
+    about: "Generates a set of different random openings for tournaments, SPRT tests and self-play.\n\nUse \
+            `--balance` to keep only openings that a shallow search scores close to even.",
+    options: &[
+        Opt::value("count", "N", "How many openings to generate (default 100)").short('n'),
+        ROWS,
+        COLS,
+        SIZE,
+        OPENING_MOVES,
+        BALANCE,
+        Opt::value("depth", "N", "How far ahead `--balance` searches (default 4)").short('d'),
+        VARIANT,
+        TOPOLOGY,
+        SETUP,
+        LAYOUT,
+        GRID,
+        CAPTURES,
+        SHAPE,
+        SEED,
+    ],
+};
+
+/// Parses two bot specs for commands where nobody is at the keyboard.
+fn bot_specs(matches: &Matches) -> (PlayerSpec, PlayerSpec) {
+    let positionals = matches.positionals();
+    let specs = (
+        player_spec(&positionals[0], matches, 0),
+        player_spec(&positionals[1], matches, 1),
+    );
+    if !specs.0.kind.is_bot() || !specs.1.kind.is_bot() {
+        fail(3, "Both players need to be bots.");
+    }
+    specs
+}
+
+/// Reads the openings in `--book`, or generates `count` openings of `moves` random moves that pass `--balance`.
+///
+/// Returns `None` when no openings are wanted.
+fn opening_set(
+    matches: &Matches,
+    rules: &Rules,
+    (rows, cols): (usize, usize),
+    count: usize,
+    moves: usize,
+    seed: u64,
+) -> Option<Vec<Opening>> {
+    if let Some(path) = matches.value("book") {
+        let set = OpeningSet::load(path, rules).unwrap_or_else(|err| {
+            fail(2, &format!("Couldn't read openings from {}: {}", path, err))
+        });
+        if (set.rows, set.cols) != (rows, cols) {
+            fail(
+                1,
+                &format!(
+                    "The openings in {} are for {}x{} boards, not {}x{}.",
+                    path, set.rows, set.cols, rows, cols
+                ),
+            );
+        }
+        if set.openings.is_empty() {
+            fail(2, &format!("{} has no openings.", path));
+        }
+        return Some(set.openings);
+    }
+    if count == 0 || moves == 0 {
+        return None;
+    }
+
+    let mut generator = Generator::new(rows, cols, moves);
+    generator.balance = option(matches.get::<isize>("balance"));
+    generator.seed = seed;
+    generator.rules = rules.clone();
+    let set = generator
+        .generate(count)
+        .unwrap_or_else(|err| fail(1, &err));
+    if set.openings.is_empty() {
+        fail(
+            2,
+            "Couldn't find any openings that are close enough to even.",
+        );
+    }
+    Some(set.openings)
+}
+
+/// `othello selfplay`
+pub fn selfplay(args: &[String]) {
+    let matches = parse(&SELFPLAY, args, 3..=3);
+    let specs = bot_specs(&matches);
+    let games = option(matches.get::<usize>("games")).unwrap_or(100);
+    let (rows, cols) = board_size(&matches, 8);
+
+    let mut config = SelfPlay::new(specs, games, rows, cols);
+    if let Some(random_moves) = option(matches.get::<usize>("random-moves")) {
+        config.random_moves = random_moves;
+    }
+    config.seed = option(matches.get::<u64>("seed"))
+        .unwrap_or_else(|| othlib::rng::Rng::from_time().next_u64());
+    config.rules = rules(&matches, (rows, cols));
+    if matches.value("book").is_some() || matches.value("balance").is_some() {
+        let openings = opening_set(
+            &matches,
+            &config.rules,
+            (rows, cols),
+            games,
+            config.random_moves,
+            config.seed,
+        );
+        config.openings = openings.unwrap_or_default();
+    }
+    // Solve the last few moves exactly, which is cheap and gives the trainer a second label to use
+    config.solve_empties = Some(option(matches.get::<usize>("solve-empties")).unwrap_or(8));
+    if let Some(threads) = option(matches.get::<usize>("threads")) {
+        config.threads = threads.max(1);
+    }
+
+    let path = &matches.positionals()[2];
+    let written = File::create(path).and_then(|file| config.run(BufWriter::new(file)));
+    match written {
+        Ok(written) => println!("Wrote {} positions from {} games", written, games),
+        Err(err) => fail(2, &format!("Couldn't write self-play data: {}", err)),
+    }
+}
+
+/// `othello tournament`
+pub fn tournament(args: &[String]) {
+    let matches = parse(&TOURNAMENT, args, 2..=usize::MAX);
+    let players = matches
+        .positionals()
+        .iter()
+        .enumerate()
+        .map(|(index, spec)| player_spec(spec, &matches, index as u64))
+        .collect::<Vec<_>>();
+    if players.iter().any(|spec| !spec.kind.is_bot()) {
+        fail(3, "Every player in a tournament needs to be a bot.");
+    }
+    let (rows, cols) = board_size(&matches, 8);
+
+    let mut tournament = Tournament::new(players, rows, cols);
+    let seed = option(matches.get::<u64>("seed"))
+        .unwrap_or_else(|| othlib::rng::Rng::from_time().next_u64());
+    let count = option(matches.get::<usize>("openings")).unwrap_or(4);
+    let moves = option(matches.get::<usize>("opening-moves")).unwrap_or(4);
+    tournament.rules = rules(&matches, (rows, cols));
+    if let Some(openings) = opening_set(
+        &matches,
+        &tournament.rules,
+        (rows, cols),
+        count,
+        moves,
+        seed,
+    ) {
+        tournament.openings = openings;
+    }
+    if let Some(threads) = option(matches.get::<usize>("threads")) {
+        tournament.threads = threads.max(1);
+    }
+
+    let total = tournament.schedule().len();
+    let mut finished = 0;
+    let standings = tournament
+        .run(|result| {
+            finished += 1;
+            let pairing = result.pairing;
+            eprintln!(
+                "Game {}/{}: {} (X) vs {} (O): {}",
+                finished,
+                total,
+                tournament.players[pairing.black],
+                tournament.players[pairing.white],
+                match result.winner {
+                    0 => "draw".to_string(),
+                    x => format!("player {} wins by {}", x, result.differential.abs()),
+                }
+            );
+        })
+        .unwrap_or_else(|err| fail(2, &err));
+
+    println!("\n{}", standings.table());
+    if let Some(path) = matches.value("csv") {
+        if let Err(err) = std::fs::write(path, standings.to_csv()) {
+            fail(2, &format!("Couldn't write the results: {}", err));
+        }
+    }
+}
+
+/// `othello sprt`
+pub fn sprt(args: &[String]) {
+    let matches = parse(&SPRT, args, 2..=2);
+    let specs = bot_specs(&matches);
+    let (rows, cols) = board_size(&matches, 8);
+    let defaults = Sprt::default();
+    let sprt = Sprt::new(
+        option(matches.get::<f64>("elo0")).unwrap_or(defaults.elo0),
+        option(matches.get::<f64>("elo1")).unwrap_or(defaults.elo1),
+        option(matches.get::<f64>("alpha")).unwrap_or(defaults.alpha),
+        option(matches.get::<f64>("beta")).unwrap_or(defaults.beta),
+    )
+    .unwrap_or_else(|err| fail(1, &err));
+
+    let mut test = Match::new(specs, rows, cols, sprt);
+    let seed = option(matches.get::<u64>("seed"))
+        .unwrap_or_else(|| othlib::rng::Rng::from_time().next_u64());
+    let moves = option(matches.get::<usize>("opening-moves")).unwrap_or(4);
+    test.rules = rules(&matches, (rows, cols));
+    if let Some(openings) = opening_set(&matches, &test.rules, (rows, cols), 1000, moves, seed) {
+        test.openings = openings;
+    }
+    test.max_pairs = option(matches.get::<usize>("max-games")).map(|games| games.div_ceil(2));
+    if let Some(threads) = option(matches.get::<usize>("threads")) {
+        test.threads = threads.max(1);
+    }
+
+    let status = test
+        .run(|status| eprint!("\r{}    ", status))
+        .unwrap_or_else(|err| fail(2, &err));
+    eprintln!();
+    println!("{}", status);
+    match status.verdict {
+        Some(Verdict::H1) => println!("{} is stronger than {}.", test.players.0, test.players.1),
+        Some(Verdict::H0) => println!("{} isn't stronger than {}.", test.players.0, test.players.1),
+        None => println!("The test stopped before reaching a result."),
+    }
+}
+
+/// `othello openings`
+pub fn openings(args: &[String]) {
+    let matches = parse(&OPENINGS, args, 1..=1);
+    let (rows, cols) = board_size(&matches, 8);
+    let count = option(matches.get::<usize>("count")).unwrap_or(100);
+
+    let mut generator = Generator::new(
+        rows,
+        cols,
+        option(matches.get::<usize>("opening-moves")).unwrap_or(4),
+    );
+    generator.balance = option(matches.get::<isize>("balance"));
+    if let Some(depth) = option(matches.get::<usize>("depth")) {
+        generator.depth = depth;
+    }
+    generator.seed = option(matches.get::<u64>("seed"))
+        .unwrap_or_else(|| othlib::rng::Rng::from_time().next_u64());
+    generator.rules = rules(&matches, (rows, cols));
+
+    let set = generator
+        .generate(count)
+        .unwrap_or_else(|err| fail(1, &err));
+    let path = &matches.positionals()[0];
+    if let Err(err) = set.save(path) {
+        fail(2, &format!("Couldn't write openings: {}", err));
+    }
+    println!(
+        "Wrote {} of {} openings to {}",
+        set.openings.len(),
+        count,
+        path
+    );
+}
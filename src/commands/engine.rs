@@ -0,0 +1,70 @@
+//! Letting other programs drive a bot, over stdin and stdout or HTTP.
+
+use super::{fail, option, parse, PORT};
+use othlib::cli::{Command, Opt};
+use othlib::nboard::NBoardEngine;
+use othlib::player::spec::PlayerSpec;
+use othlib::service::GameService;
+
+use std::net::TcpListener;
+
+pub const ENGINE: Command = Command {
+    name: "engine",
+    usage: "[OPTIONS] [PLAYER]",
+    about: "Runs a bot as an engine on stdin and stdout for a GUI or another program (default minimax:depth=6).",
+    options: &[Opt::value("protocol", "nboard|line", "The protocol to speak (default nboard)")],
+};
+
+pub const SERVE: Command = Command {
+    name: "serve",
+    usage: "[OPTIONS] [ENGINE]",
+    about: "Hosts games over HTTP on 127.0.0.1. The engine answers /best-move (default minimax:depth=6).",
+    options: &[PORT],
+};
+
+/// `othello engine`
+pub fn engine(args: &[String]) {
+    let matches = parse(&ENGINE, args, 0..=1);
+    let spec = matches
+        .positionals()
+        .first()
+        .map_or("minimax:depth=6", String::as_str)
+        .parse::<PlayerSpec>()
+        .unwrap_or_else(|err| fail(3, &err));
+    let stdin = std::io::stdin();
+
+    let result = match matches.value("protocol").unwrap_or("nboard") {
+        "nboard" => {
+            let mut engine = NBoardEngine::new(spec).unwrap_or_else(|err| fail(3, &err));
+            engine.run(stdin.lock(), std::io::stdout())
+        }
+        "line" => othlib::protocol::serve(&spec, stdin.lock(), std::io::stdout()),
+        other => fail(
+            1,
+            &format!("`{}` isn't a protocol, use `nboard` or `line`.", other),
+        ),
+    };
+    if let Err(err) = result {
+        fail(2, &format!("The engine stopped: {}", err));
+    }
+}
+
+/// `othello serve`
+pub fn serve(args: &[String]) {
+    let matches = parse(&SERVE, args, 0..=1);
+    let port = option(matches.get::<u16>("port")).unwrap_or(8080);
+    let engine = matches
+        .positionals()
+        .first()
+        .map_or("minimax:depth=6", String::as_str)
+        .parse::<PlayerSpec>()
+        .unwrap_or_else(|err| fail(3, &err));
+
+    // Only local clients: the service is meant to sit behind other tools, not face the network
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .unwrap_or_else(|err| fail(2, &format!("Couldn't listen on port {}: {}", port, err)));
+    println!("Serving games on http://127.0.0.1:{}", port);
+    if let Err(err) = GameService::new(engine).serve(&listener) {
+        fail(2, &format!("The service stopped: {}", err));
+    }
+}
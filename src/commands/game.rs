@@ -0,0 +1,226 @@
+//! Playing games against people and bots, on this machine or over the network.
+
+use super::{
+    board_size, fail, option, parse, player_spec, print_result, rules, CAPTURES, COLS, GRID,
+    LAYOUT, LEVEL, PORT, ROWS, SEED, SETUP, SHAPE, SIZE, SWAP, THINK, TIME, TOPOLOGY, VARIANT,
+};
+use othlib::cli::{Command, Opt};
+use othlib::net::RemotePlayer;
+use othlib::player::spec::PlayerKind;
+use othlib::rolit::Rolit;
+use othlib::rules::{Grid, Rules};
+use othlib::{tui, ActivePlayer};
+
+use std::net::{TcpListener, TcpStream};
+
+pub const PLAY: Command = Command {
+    name: "play",
+    usage: "[OPTIONS] [PLAYER] [PLAYER]",
+    about: "Plays a game. Player one defaults to `human` and player two to `minimax:5`.\n\nPlayers are \
+            `human`, `minimax[:options]` or `external:cmd=<command>`, see the README for their options.",
+    options: &[
+        ROWS,
+        COLS,
+        SIZE,
+        LEVEL,
+        TIME,
+        SEED,
+        Opt::value("symbols", "XO", "The symbols of player one and two (default XO)"),
+        Opt::value("first", "1|2", "Which player moves first (default 1)"),
+        VARIANT,
+        TOPOLOGY,
+        SETUP,
+        LAYOUT,
+        GRID,
+        CAPTURES,
+        SHAPE,
+        SWAP,
+        THINK,
+        Opt::flag("plain", "Prompt for rows and columns instead of using the full screen"),
+    ],
+};
+
+pub const ROLIT: Command = Command {
+    name: "rolit",
+    usage: "[OPTIONS] <PLAYER> <PLAYER> [PLAYER] [PLAYER]",
+    about: "Plays Rolit, Othello for two to four players.\n\nPlayers are `human`, `maxn[:DEPTH]` or \
+            `paranoid[:DEPTH]` (default depth 3), and play R, Y, G and B in the order they're given. A move has to \
+            capture if it can, otherwise it can go next to any disc.",
+    options: &[ROWS, COLS, SIZE],
+};
+
+pub const HOST: Command = Command {
+    name: "host",
+    usage: "[OPTIONS] [PLAYER]",
+    about: "Hosts a game for a player on another machine. The host plays X (default human) and moves first.",
+    options: &[PORT, ROWS, COLS, SIZE, LEVEL, TIME, SEED, THINK],
+};
+
+pub const JOIN: Command = Command {
+    name: "join",
+    usage: "[OPTIONS] <HOST[:PORT]> [PLAYER]",
+    about: "Joins a hosted game as O (default human).",
+    options: &[LEVEL, TIME, SEED, THINK],
+};
+
+/// `othello play`
+pub fn play(args: &[String]) {
+    let matches = parse(&PLAY, args, 0..=2);
+    let positionals = matches.positionals();
+    let specs = (
+        player_spec(
+            positionals.first().map_or("human", String::as_str),
+            &matches,
+            0,
+        ),
+        player_spec(
+            positionals.get(1).map_or("minimax:5", String::as_str),
+            &matches,
+            1,
+        ),
+    );
+    let (rows, cols) = board_size(&matches, 8);
+
+    let symbols = matches
+        .value("symbols")
+        .unwrap_or("XO")
+        .chars()
+        .collect::<Vec<_>>();
+    let symbols = match symbols.as_slice() {
+        &[one, two]
+            if one != two
+                && [one, two]
+                    .iter()
+                    .all(|x| !"?.".contains(*x) && !x.is_whitespace()) =>
+        {
+            (one, two)
+        }
+        _ => fail(
+            1,
+            "`--symbols` needs two different characters, and neither can be `.`, `?` or a space.",
+        ),
+    };
+    let first = match matches.value("first").unwrap_or("1") {
+        "1" | "one" => ActivePlayer::PlayerOne,
+        "2" | "two" => ActivePlayer::PlayerTwo,
+        other => fail(1, &format!("`--first` must be 1 or 2, not `{}`.", other)),
+    };
+
+    let player_one = specs.0.build(symbols.0).unwrap_or_else(|err| fail(3, &err));
+    let player_two = specs.1.build(symbols.1).unwrap_or_else(|err| fail(3, &err));
+    let mut game = rules(&matches, (rows, cols))
+        .start(&*player_one, &*player_two, rows, cols)
+        .unwrap_or_else(|err| fail(1, &err));
+    if first == ActivePlayer::PlayerTwo {
+        game.change_active_player();
+    }
+
+    // Humans get the full-screen interface when there's a terminal to draw it on
+    let humans = (
+        specs.0.kind == PlayerKind::Human,
+        specs.1.kind == PlayerKind::Human,
+    );
+    // The full-screen board is only drawn with square cells
+    if (humans.0 || humans.1)
+        && !matches.flag("plain")
+        && game.grid() == Grid::Square
+        && tui::is_supported()
+    {
+        match tui::run(game.clone(), humans) {
+            Ok(played) => game = played,
+            Err(err) => {
+                eprintln!("Couldn't start the full-screen interface ({}).", err);
+                game.run();
+            }
+        }
+    } else {
+        game.run();
+    }
+
+    print_result(&game);
+}
+
+/// `othello rolit`
+pub fn rolit(args: &[String]) {
+    let matches = parse(&ROLIT, args, 2..=othlib::rolit::COLORS.len());
+    let (rows, cols) = board_size(&matches, 8);
+    let players = matches
+        .positionals()
+        .iter()
+        .zip(othlib::rolit::COLORS)
+        .map(|(spec, color)| {
+            othlib::rolit::player_from_spec(spec, color).unwrap_or_else(|err| fail(3, &err))
+        })
+        .collect::<Vec<_>>();
+
+    let mut game = Rolit::with_players(players.iter().map(Box::as_ref).collect(), rows, cols)
+        .unwrap_or_else(|err| fail(1, &err));
+    game.run(true);
+    println!(
+        "\n================================\n{}\n================================\n\nBoard: \n{}",
+        game.get_winner()
+            .unwrap_or_else(|| "The game was stopped with moves left.".to_string()),
+        game
+    );
+}
+
+/// `othello host`
+pub fn host(args: &[String]) {
+    let matches = parse(&HOST, args, 0..=1);
+    let spec = player_spec(
+        matches
+            .positionals()
+            .first()
+            .map_or("human", String::as_str),
+        &matches,
+        0,
+    );
+    let port = option(matches.get::<u16>("port")).unwrap_or(othlib::net::DEFAULT_PORT);
+    let (rows, cols) = board_size(&matches, 8);
+    let rules = Rules::default();
+    if let Err(err) = rules.grid.board_size(rows, cols) {
+        fail(1, &err);
+    }
+    let local = spec.build('X').unwrap_or_else(|err| fail(3, &err));
+
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .unwrap_or_else(|err| fail(2, &format!("Couldn't listen on port {}: {}", port, err)));
+    println!("Waiting for a player to join on port {}...", port);
+    let remote = RemotePlayer::accept(&listener, ('X', 'O'), ActivePlayer::PlayerTwo)
+        .unwrap_or_else(|err| fail(2, &format!("Couldn't start the game: {}", err)));
+
+    let mut game = rules
+        .start(&*local, &remote, rows, cols)
+        .unwrap_or_else(|err| fail(1, &err));
+    othlib::net::host(&mut game, &remote, true);
+    if !remote.is_connected() {
+        println!("The other player disconnected.");
+    }
+    print_result(&game);
+}
+
+/// `othello join`
+pub fn join(args: &[String]) {
+    let matches = parse(&JOIN, args, 1..=2);
+    let positionals = matches.positionals();
+    let spec = player_spec(
+        positionals.get(1).map_or("human", String::as_str),
+        &matches,
+        1,
+    );
+    let address = if positionals[0].contains(':') {
+        positionals[0].clone()
+    } else {
+        format!("{}:{}", positionals[0], othlib::net::DEFAULT_PORT)
+    };
+
+    let result =
+        TcpStream::connect(&address).and_then(|stream| othlib::net::join(stream, &spec, true));
+    match result {
+        Ok(result) => println!(
+            "\n================================\n{}\n================================",
+            result
+        ),
+        Err(err) => fail(2, &format!("The game ended early: {}", err)),
+    }
+}
@@ -0,0 +1,364 @@
+//! The subcommands of the `othello` binary.
+//!
+//! Each module holds a group of related commands: their `Command` descriptions next to the functions that run them.
+//! The options and helpers they share are here.
+
+pub mod analysis;
+pub mod bots;
+pub mod engine;
+pub mod game;
+pub mod render;
+pub mod train;
+
+use othlib::board::{Board, Mask};
+use othlib::cli::{Command, Matches, Opt};
+use othlib::player::spec::{PlayerKind, PlayerSpec};
+use othlib::rules::{Captures, Grid, Layout, Rules, Setup, Topology, Variant};
+use othlib::{ActivePlayer, Othello};
+
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::time::Duration;
+
+/// The most columns a board can have. Columns are named by letter in NBoard's notation, which runs out at `z`.
+const MAX_COLS: usize = 26;
+
+/// The most rows a board can have, the same as columns so a board can always be turned on its side.
+const MAX_ROWS: usize = MAX_COLS;
+
+/// How long minimax players without a level, depth or time limit think per move. Searches that reach the end of
+/// the game stop early, so they still play perfectly on small boards.
+const DEFAULT_TIME: Duration = Duration::from_secs(1);
+
+const ROWS: Opt = Opt::value("rows", "N", "Board rows, up to 26 (default 8)").short('r');
+
+const COLS: Opt = Opt::value("cols", "N", "Board columns, up to 26 (default 8)").short('c');
+
+const SIZE: Opt = Opt::value("size", "N", "Sets both rows and columns").short('s');
+
+const LEVEL: Opt = Opt::value(
+    "level",
+    "1-10",
+    "Strength of minimax players that don't pick their own",
+)
+.short('l');
+
+const TIME: Opt = Opt::value(
+    "time",
+    "MS",
+    "Time limit per move for minimax players (default 1000 without a level or depth)",
+)
+.short('t');
+
+const SEED: Opt = Opt::value(
+    "seed",
+    "N",
+    "Seed for the players' randomness, for repeatable games",
+);
+
+const THINK: Opt = Opt::flag("think", "Print what minimax players find while they search");
+
+const GAMES: Opt = Opt::value("games", "N", "How many games to play").short('n');
+
+const THREADS: Opt = Opt::value("threads", "N", "Worker threads (default: one per core)");
+
+const OPENING_MOVES: Opt = Opt::value(
+    "opening-moves",
+    "N",
+    "Random moves in each opening (default 4)",
+);
+
+const BOOK: Opt = Opt::value(
+    "book",
+    "FILE",
+    "Start games from the openings in a file written by `othello openings`",
+);
+
+const BALANCE: Opt = Opt::value(
+    "balance",
+    "SCORE",
+    "Only use openings a shallow search scores within SCORE of even",
+);
+
+const VARIANT: Opt = Opt::value(
+    "variant",
+    "standard|misere",
+    "The rules: `misere` is won by the player with fewer discs (default standard)",
+);
+
+const TOPOLOGY: Opt = Opt::value(
+    "topology",
+    "flat|torus",
+    "`torus` wraps lines around the edges of the board (default flat)",
+);
+
+const SETUP: Opt = Opt::value(
+    "setup",
+    "fixed|free",
+    "`free` starts with an empty board and the players place the middle four discs (default fixed)",
+);
+
+const LAYOUT: Opt = Opt::value(
+    "layout",
+    "diagonal|parallel",
+    "`parallel` starts each player's discs side by side instead of crossed (default diagonal)",
+);
+
+const GRID: Opt = Opt::value(
+    "grid",
+    "square|hex|cube",
+    "`hex` plays on hexagonal cells, on a hexagon as many cells across as the (odd) size, and `cube` on a cube \
+     of that size (default square)",
+);
+
+const CAPTURES: Opt = Opt::value(
+    "captures",
+    "all|orthogonal|diagonal",
+    "Which lines capture: only along rows and columns, only diagonally, or both (default all)",
+);
+
+const SHAPE: Opt = Opt::value(
+    "shape",
+    "SHAPE",
+    "`octagon`, `obstacles[:N]` (N random blocked cells), or a layout of `.` and `#` rows (or a file with one)",
+);
+
+const SWAP: Opt = Opt::flag(
+    "swap",
+    "Let the second player swap colors after the first move instead of moving (the pie rule)",
+);
+
+const PORT: Opt = Opt::value("port", "PORT", "The port to listen on").short('p');
+
+const COMMANDS: &[Command] = &[
+    game::PLAY,
+    game::ROLIT,
+    analysis::ANALYZE,
+    analysis::SOLVE,
+    bots::SELFPLAY,
+    bots::TOURNAMENT,
+    bots::SPRT,
+    bots::OPENINGS,
+    render::RENDER,
+    train::TRAIN,
+    engine::ENGINE,
+    game::HOST,
+    game::JOIN,
+    engine::SERVE,
+];
+
+/// Prints `message` and exits. Codes are 1 for bad arguments, 2 for failures while running and 3 for bad players.
+fn fail(code: i32, message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(code);
+}
+
+/// Parses the arguments of `command`, printing its help and exiting if that's what was asked for.
+fn parse(command: &Command, args: &[String], positionals: RangeInclusive<usize>) -> Matches {
+    let matches = command.parse(args).unwrap_or_else(|err| fail(1, &err));
+    if matches.flag("help") {
+        print!("{}", command.help());
+        std::process::exit(0);
+    }
+    if !positionals.contains(&matches.positionals().len()) {
+        fail(
+            1,
+            &format!(
+                "Usage: othello {} {}\nSee `othello {} --help` for more.",
+                command.name, command.usage, command.name
+            ),
+        );
+    }
+    matches
+}
+
+/// Unwraps a parsed option, exiting with its error.
+fn option<T>(value: Result<Option<T>, String>) -> Option<T> {
+    value.unwrap_or_else(|err| fail(1, &err))
+}
+
+/// Reads `--size`, `--rows` and `--cols`.
+///
+/// Only the row and column limits are checked here. Whether the rules can start a game on the board is up to
+/// `Rules::start`, or `Grid::board_size` before anything is built for it.
+fn board_size(matches: &Matches, default: usize) -> (usize, usize) {
+    let size = option(matches.get::<usize>("size")).unwrap_or(default);
+    let rows = option(matches.get::<usize>("rows")).unwrap_or(size);
+    let cols = option(matches.get::<usize>("cols")).unwrap_or(size);
+    if rows > MAX_ROWS {
+        fail(
+            1,
+            &format!(
+                "The board can have at most {} rows, not {}.",
+                MAX_ROWS, rows
+            ),
+        );
+    }
+    if cols > MAX_COLS {
+        fail(
+            1,
+            &format!(
+                "The board can have at most {} columns, not {}.",
+                MAX_COLS, cols
+            ),
+        );
+    }
+    (rows, cols)
+}
+
+/// Reads `--variant`.
+fn variant(matches: &Matches) -> Variant {
+    option(matches.get::<Variant>("variant")).unwrap_or_default()
+}
+
+/// Reads `--topology`.
+fn topology(matches: &Matches) -> Topology {
+    option(matches.get::<Topology>("topology")).unwrap_or_default()
+}
+
+/// Reads `--setup`.
+fn setup(matches: &Matches) -> Setup {
+    option(matches.get::<Setup>("setup")).unwrap_or_default()
+}
+
+/// Reads `--layout`.
+fn layout(matches: &Matches) -> Layout {
+    option(matches.get::<Layout>("layout")).unwrap_or_default()
+}
+
+/// Reads `--grid`.
+fn grid(matches: &Matches) -> Grid {
+    option(matches.get::<Grid>("grid")).unwrap_or_default()
+}
+
+/// Reads `--captures`.
+fn captures(matches: &Matches) -> Captures {
+    option(matches.get::<Captures>("captures")).unwrap_or_default()
+}
+
+/// Reads `--variant`, `--topology`, `--setup`, `--layout`, `--grid`, `--captures` and `--shape` for a board of `rows` by `cols`.
+///
+/// Exits if the grid doesn't fit the board, before a shape is made for it.
+fn rules(matches: &Matches, (rows, cols): (usize, usize)) -> Rules {
+    let grid = grid(matches);
+    if let Err(err) = grid.board_size(rows, cols) {
+        fail(1, &err);
+    }
+    let mask = matches.value("shape").and_then(|shape| {
+        let mask = match shape.split_once(':').unwrap_or((shape, "")) {
+            ("square", "") => return None,
+            ("octagon", "") => Mask::octagon(rows, cols),
+            ("obstacles", count) => {
+                let count = match count {
+                    "" => rows * cols / 10,
+                    count => count.parse().unwrap_or_else(|_| {
+                        fail(1, &format!("`{}` isn't a number of obstacles.", count))
+                    }),
+                };
+                let seed = option(matches.get::<u64>("seed"))
+                    .unwrap_or_else(|| othlib::rng::Rng::from_time().next_u64());
+                Mask::obstacles(rows, cols, count, seed)
+            }
+            _ if Path::new(shape).is_file() => std::fs::read_to_string(shape)
+                .map_err(|err| format!("Couldn't read {}: {}", shape, err))
+                .and_then(|layout| layout.parse())
+                .unwrap_or_else(|err| fail(1, &err)),
+            _ => shape.parse().unwrap_or_else(|err: String| {
+                fail(1, &format!("`{}` isn't a shape. {}", shape, err))
+            }),
+        };
+        if (mask.rows(), mask.cols()) != (rows, cols) {
+            fail(
+                1,
+                &format!(
+                    "The shape is {}x{} but the board is {}x{}, set `--rows` and `--cols` to match.",
+                    mask.rows(),
+                    mask.cols(),
+                    rows,
+                    cols
+                ),
+            );
+        }
+        Some(mask)
+    });
+    Rules {
+        variant: variant(matches),
+        mask,
+        topology: topology(matches),
+        setup: setup(matches),
+        layout: layout(matches),
+        grid,
+        captures: captures(matches),
+        swap: matches.flag("swap"),
+    }
+}
+
+/// Parses a player spec and fills in what the command line options set for every player.
+///
+/// `--level` only applies to minimax players without a level or depth of their own, `--time` to minimax players
+/// without their own and `--seed` to levels without their own. Minimax players still without any limit think for
+/// `DEFAULT_TIME`. `seed_offset` keeps the players of one game from sharing a seed.
+fn player_spec(text: &str, matches: &Matches, seed_offset: u64) -> PlayerSpec {
+    let mut spec = text
+        .parse::<PlayerSpec>()
+        .unwrap_or_else(|err| fail(3, &err));
+    if spec.kind != PlayerKind::Minimax {
+        return spec;
+    }
+
+    if spec.level.is_none() && spec.depth.is_none() {
+        spec.level = option(matches.get::<u8>("level"));
+        if let Some(level) = spec.level {
+            // Reuse the spec parser's range check and message
+            format!("minimax:{}", level)
+                .parse::<PlayerSpec>()
+                .unwrap_or_else(|err| fail(1, &err));
+        }
+    }
+    if spec.time.is_none() {
+        spec.time = option(matches.get::<u64>("time")).map(Duration::from_millis);
+    }
+    if spec.level.is_none() && spec.depth.is_none() && spec.time.is_none() {
+        spec.time = Some(DEFAULT_TIME);
+    }
+    // Only levels play randomly
+    if spec.seed.is_none() && spec.level.is_some() {
+        spec.seed = option(matches.get::<u64>("seed")).map(|seed| seed.wrapping_add(seed_offset));
+    }
+    spec.think |= matches.flag("think");
+    spec
+}
+
+/// Reads a board and the side to move.
+fn position(board: &str, side: &str) -> (Board, ActivePlayer) {
+    let board = Board::decode(board)
+        .unwrap_or_else(|| fail(1, "Every row of the board must be the same length."));
+    let active = match side {
+        "X" => ActivePlayer::PlayerOne,
+        "O" => ActivePlayer::PlayerTwo,
+        _ => fail(1, "The side to move must be `X` or `O`."),
+    };
+    (board, active)
+}
+
+fn print_result(game: &Othello) {
+    println!(
+        "\n================================\n{}\n================================\n\nBoard: \n{}",
+        game.get_winner()
+            .unwrap_or_else(|| "The game was stopped with moves left.".to_string()),
+        game
+    );
+}
+
+pub fn help() -> String {
+    let mut help = "Othello against people, bots and other programs.\n\nUsage: othello <COMMAND> [OPTIONS]\n       \
+                    othello [--think] [--plain] <PLAYER> <PLAYER> [ROWS] [COLS]\n\nCommands:\n"
+        .to_string();
+    for command in COMMANDS {
+        let about = command.about.lines().next().unwrap_or("");
+        let about = about.split(". ").next().unwrap_or("").trim_end_matches('.');
+        help += &format!("  {:<12}{}\n", command.name, about);
+    }
+    help += "\nSee `othello <COMMAND> --help` for the options of each command.\n";
+    help
+}
@@ -0,0 +1,126 @@
+//! Drawing positions and games as SVG.
+
+use super::{
+    board_size, captures, fail, grid, option, parse, position, rules, CAPTURES, COLS, GRID, LAYOUT,
+    ROWS, SIZE,
+};
+use othlib::cli::{Command, Matches, Opt};
+use othlib::player::minimax::{parse_pv, MinimaxPlayer};
+use othlib::svg::{self, Diagram};
+use othlib::{ActivePlayer, Othello};
+
+pub const RENDER: Command = Command {
+    name: "render",
+    usage: "[OPTIONS] <OUT>",
+    about: "Draws a position or a whole game as SVG.\n\nWith `--moves` the game is written as one animated SVG, or \
+            as numbered frames in the OUT directory with `--frames`. Otherwise OUT is a diagram of `--board` (or the \
+            starting position).",
+    options: &[
+        Opt::value("board", "BOARD", "The position to draw, written as for `analyze`"),
+        Opt::value("side", "X|O", "The side to move in `--board` (default X)"),
+        Opt::value("last", "ROW,COL", "Mark a move as the last one played"),
+        Opt::value("preview", "ROW,COL", "Play a move for the side to move and ring the discs it flips"),
+        Opt::value("moves", "MOVES", "A game record like `2,4 2,5 pass 3,5`, played from the start"),
+        Opt::flag("legal", "Draw dots on the legal moves of the side to move"),
+        Opt::flag("frames", "Write numbered frames instead of one animated SVG"),
+        Opt::value("delay", "MS", "How long each move is shown in animations (default 1000)"),
+        Opt::value("cell", "PX", "The width of a cell in pixels (default 40)"),
+        LAYOUT,
+        GRID,
+        CAPTURES,
+        ROWS,
+        COLS,
+        SIZE,
+    ],
+};
+
+/// Reads a `ROW,COL` option.
+fn cell(matches: &Matches, name: &str) -> Option<(usize, usize)> {
+    let value = matches.value(name)?;
+    match parse_pv(value).as_deref() {
+        Some([Some(cell)]) => Some(*cell),
+        _ => fail(
+            1,
+            &format!(
+                "`--{}` needs a row and column like `2,3`, not `{}`.",
+                name, value
+            ),
+        ),
+    }
+}
+
+/// `othello render`
+pub fn render(args: &[String]) {
+    let matches = parse(&RENDER, args, 1..=1);
+    let out = &matches.positionals()[0];
+    let cell_size = option(matches.get::<usize>("cell")).unwrap_or(40);
+    let legal = matches.flag("legal");
+
+    if let Some(moves) = matches.value("moves") {
+        let moves = parse_pv(moves).unwrap_or_else(|| {
+            fail(
+                1,
+                "Moves are written as `row,col` or `pass`, separated by spaces.",
+            )
+        });
+        let (rows, cols) = board_size(&matches, 8);
+        let frames = svg::replay(
+            &rules(&matches, (rows, cols)),
+            rows,
+            cols,
+            ('X', 'O'),
+            &moves,
+            legal,
+        )
+        .unwrap_or_else(|err| fail(1, &err))
+        .into_iter()
+        .map(|frame| frame.with_cell_size(cell_size))
+        .collect::<Vec<_>>();
+
+        let written = if matches.flag("frames") {
+            svg::write_frames(out, &frames).map(|_| ())
+        } else {
+            let delay = option(matches.get::<u64>("delay")).unwrap_or(1000);
+            std::fs::write(out, svg::animate(&frames, delay as f64 / 1000.0))
+        };
+        match written {
+            Ok(()) => println!("Wrote {} positions to {}", frames.len(), out),
+            Err(err) => fail(2, &format!("Couldn't write {}: {}", out, err)),
+        }
+        return;
+    }
+
+    let (board, active) = match matches.value("board") {
+        Some(board) => position(board, matches.value("side").unwrap_or("X")),
+        None => {
+            let (rows, cols) = board_size(&matches, 8);
+            let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+            let game = rules(&matches, (rows, cols))
+                .start(&one, &two, rows, cols)
+                .unwrap_or_else(|err| fail(1, &err));
+            (game.board().clone(), ActivePlayer::PlayerOne)
+        }
+    };
+    let (player_one, player_two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    let mut game = Othello::with_board(&player_one, &player_two, board, active)
+        .with_grid(grid(&matches))
+        .with_captures(captures(&matches));
+
+    let mut last = cell(&matches, "last");
+    let mut flips = vec![];
+    if let Some((row, col)) = cell(&matches, "preview") {
+        flips = game.flips(row, col, game.get_active_symbol());
+        game.try_move(row, col).unwrap_or_else(|err| fail(1, &err));
+        last = Some((row, col));
+    }
+    let mut diagram = Diagram::of_game(&game)
+        .with_last_move(last)
+        .with_flips(flips)
+        .with_cell_size(cell_size);
+    if legal {
+        diagram = diagram.with_legal_moves(game.successors(game.get_active_symbol()));
+    }
+    if let Err(err) = std::fs::write(out, diagram.render()) {
+        fail(2, &format!("Couldn't write {}: {}", out, err));
+    }
+}
@@ -0,0 +1,53 @@
+//! Fitting pattern weights to training data.
+
+use super::{fail, option, parse};
+use othlib::cli::{Command, Opt};
+use othlib::eval::pattern::PatternWeights;
+use othlib::eval::train::{load_samples, Trainer};
+
+const PHASES: usize = 4;
+
+pub const TRAIN: Command = Command {
+    name: "train",
+    usage: "[OPTIONS] <DATA.csv> <WEIGHTS>",
+    about:
+        "Fits pattern weights to a CSV of labeled positions and writes them to the weights file.",
+    options: &[
+        Opt::value("target", "COLUMN", "The column to learn (default result)"),
+        Opt::value("epochs", "N", "Passes over the data (default 20)").short('e'),
+    ],
+};
+
+/// `othello train`
+pub fn train(args: &[String]) {
+    let matches = parse(&TRAIN, args, 2..=2);
+    let positionals = matches.positionals();
+    let target = matches.value("target").unwrap_or("result");
+    let samples = load_samples(&positionals[0], target)
+        .unwrap_or_else(|err| fail(2, &format!("Couldn't read training data: {}", err)));
+    let (rows, cols) = match samples.first() {
+        Some(sample) => (sample.board.rows(), sample.board.cols()),
+        None => fail(2, "The training data has no labeled positions."),
+    };
+    if let Err(err) = PatternWeights::check_size(rows, cols, PHASES) {
+        fail(2, &err);
+    }
+
+    let trainer = Trainer {
+        epochs: option(matches.get::<usize>("epochs")).unwrap_or(Trainer::default().epochs),
+        ..Trainer::default()
+    };
+    let mut weights = PatternWeights::zeroed(rows, cols, PHASES);
+    let error = trainer.train(&mut weights, &samples);
+    println!(
+        "Trained on {} positions ({}x{}), mean squared error: {:.3}",
+        samples.len(),
+        rows,
+        cols,
+        error
+    );
+
+    if let Err(err) = weights.save(&positionals[1]) {
+        fail(2, &format!("Couldn't write weights: {}", err));
+    }
+}
@@ -0,0 +1,47 @@
+//! A module for position evaluation used by the search players.
+//!
+//! The minimax player searches to the end of the game on small boards, but on anything larger it has
+//! to stop early and guess. An `Evaluate` implementation is that guess.
+
+use crate::Othello;
+
+pub mod pattern;
+pub mod train;
+
+/// # Scores a position from one player's point of view.
+///
+/// Scores are measured in discs, so an evaluation of `4` means "I expect to finish 4 discs ahead".
+/// This keeps heuristic scores comparable with the exact scores of finished games.
+pub trait Evaluate {
+    /// Returns the estimated final disc differential for `symbol` in `game`.
+    fn evaluate(&self, game: &Othello, symbol: char) -> isize;
+}
+
+/// The simplest evaluation: the current disc differential.
+pub struct DiscCount;
+
+impl Evaluate for DiscCount {
+    fn evaluate(&self, game: &Othello, symbol: char) -> isize {
+        disc_differential(game, symbol)
+    }
+}
+
+/// The result of a finished game for `symbol` under the game's variant: the disc differential, negated when the
+/// player with fewer discs wins. Positive is a win.
+pub fn outcome(game: &Othello, symbol: char) -> isize {
+    game.variant().outcome(disc_differential(game, symbol))
+}
+
+/// Counts `symbol`'s discs minus its opponent's discs.
+pub fn disc_differential(game: &Othello, symbol: char) -> isize {
+    let opponent_symbol = game.symbol_from_player(
+        !game
+            .player_from_symbol(symbol)
+            .expect("Tried to match symbol for someone not in the game."),
+    );
+    let counts = game.board().char_counts();
+    let our_count = *counts.get(&symbol).unwrap_or(&0) as isize;
+    let opponent_count = *counts.get(&opponent_symbol).unwrap_or(&0) as isize;
+
+    our_count - opponent_count
+}
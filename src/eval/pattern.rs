@@ -0,0 +1,484 @@
+//! A pattern-based evaluation with trainable weights.
+//!
+//! The board is cut into lines, edges, corners and diagonals. Each of those is a pattern instance, and
+//! every instance of the same shape shares one weight table (its class). A pattern's configuration is read
+//! as a base-3 number (empty, ours, theirs) that indexes into its class table. Separate tables are kept for
+//! each game phase, which is decided by how many discs are on the board.
+
+use crate::board::Board;
+use crate::eval::{disc_differential, Evaluate};
+use crate::Othello;
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+const HEADER: &str = "othlib-patterns 1";
+
+/// The shape a pattern class was cut from.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PatternKind {
+    /// A full outer row or column.
+    Edge,
+    /// A full inner row or column.
+    Line,
+    /// A diagonal at least three cells long.
+    Diagonal,
+    /// The block of cells around a corner, read outward from the corner.
+    Corner,
+}
+
+/// Identifies a class of pattern instances that share weights.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PatternClass {
+    pub kind: PatternKind,
+    /// The number of cells in the pattern.
+    pub len: usize,
+    /// How far the pattern is from the nearest parallel edge. Only meaningful for `Line`.
+    pub depth: usize,
+}
+
+impl PatternClass {
+    /// The number of distinct configurations (and weights) this class has.
+    pub fn configurations(&self) -> usize {
+        3usize.pow(self.len as u32)
+    }
+}
+
+/// A concrete pattern instance on the board.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    /// Index into `PatternSet::classes`.
+    pub class: usize,
+    pub squares: Vec<(usize, usize)>,
+}
+
+/// Every pattern instance for a board size.
+#[derive(Clone, Debug)]
+pub struct PatternSet {
+    rows: usize,
+    cols: usize,
+    classes: Vec<PatternClass>,
+    patterns: Vec<Pattern>,
+}
+
+impl PatternSet {
+    /// Cuts a `rows` by `cols` board into patterns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use othlib::eval::pattern::{PatternKind, PatternSet};
+    /// let set = PatternSet::for_size(4, 4);
+    /// // 4 edges, 4 inner lines, 6 diagonals and 4 corners
+    /// assert_eq!(18, set.patterns().len());
+    /// assert!(set.classes().iter().any(|class| class.kind == PatternKind::Corner));
+    /// ```
+    pub fn for_size(rows: usize, cols: usize) -> Self {
+        let mut set = Self {
+            rows,
+            cols,
+            classes: vec![],
+            patterns: vec![],
+        };
+
+        for row in 0..rows {
+            let squares = (0..cols).map(|col| (row, col)).collect();
+            set.add_line(squares, row.min(rows - 1 - row));
+        }
+        for col in 0..cols {
+            let squares = (0..rows).map(|row| (row, col)).collect();
+            set.add_line(squares, col.min(cols - 1 - col));
+        }
+
+        // Diagonals running up and to the right, then up and to the left
+        for start in 0..rows + cols - 1 {
+            let rising = (0..rows)
+                .filter_map(|row| (row + cols).checked_sub(start + 1).map(|col| (row, col)))
+                .filter(|&(_, col)| col < cols)
+                .collect::<Vec<_>>();
+            set.add_diagonal(rising);
+            let falling = (0..rows)
+                .filter_map(|row| start.checked_sub(row).map(|col| (row, col)))
+                .filter(|&(_, col)| col < cols)
+                .collect::<Vec<_>>();
+            set.add_diagonal(falling);
+        }
+
+        let size = if rows >= 3 && cols >= 3 { 3 } else { 2 };
+        if rows >= 2 && cols >= 2 {
+            for &(flip_row, flip_col) in
+                &[(false, false), (false, true), (true, false), (true, true)]
+            {
+                let mut squares = vec![];
+                for distance in 0..size {
+                    for row in 0..size {
+                        for col in 0..size {
+                            if row.max(col) != distance {
+                                continue;
+                            }
+                            let row = if flip_row { rows - 1 - row } else { row };
+                            let col = if flip_col { cols - 1 - col } else { col };
+                            squares.push((row, col));
+                        }
+                    }
+                }
+                set.add(PatternKind::Corner, squares, 0);
+            }
+        }
+
+        set
+    }
+
+    fn add_line(&mut self, squares: Vec<(usize, usize)>, depth: usize) {
+        if depth == 0 {
+            self.add(PatternKind::Edge, squares, 0);
+        } else {
+            self.add(PatternKind::Line, squares, depth);
+        }
+    }
+
+    fn add_diagonal(&mut self, squares: Vec<(usize, usize)>) {
+        if squares.len() >= 3 {
+            self.add(PatternKind::Diagonal, squares, 0);
+        }
+    }
+
+    fn add(&mut self, kind: PatternKind, squares: Vec<(usize, usize)>, depth: usize) {
+        let key = PatternClass {
+            kind,
+            len: squares.len(),
+            depth,
+        };
+        let class = match self.classes.iter().position(|&class| class == key) {
+            Some(class) => class,
+            None => {
+                self.classes.push(key);
+                self.classes.len() - 1
+            }
+        };
+        self.patterns.push(Pattern { class, squares });
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn classes(&self) -> &[PatternClass] {
+        &self.classes
+    }
+
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+
+    /// Returns the `(class, configuration)` pair of every pattern on `board` as seen by `symbol`.
+    pub fn features(&self, board: &Board, symbol: char) -> Vec<(usize, usize)> {
+        self.patterns
+            .iter()
+            .map(|pattern| {
+                let index = pattern.squares.iter().fold(0, |index, &(row, col)| {
+                    index * 3
+                        + match board.get_cell(row, col) {
+                            None => 0,
+                            Some(cell) if cell == symbol => 1,
+                            Some(_) => 2,
+                        }
+                });
+                (pattern.class, index)
+            })
+            .collect()
+    }
+}
+
+/// Weight tables for every phase and pattern class of a board size.
+#[derive(Clone, Debug)]
+pub struct PatternWeights {
+    set: PatternSet,
+    bias: Vec<f32>,
+    tables: Vec<Vec<Vec<f32>>>,
+}
+
+impl PatternWeights {
+    /// The most cells a row or column can have. A line of `n` cells needs a table of `3^n` weights.
+    pub const MAX_LINE: usize = 10;
+
+    /// Checks that weights for a board size and number of phases can be created.
+    ///
+    /// Boards need at least 2 rows and columns and at most `MAX_LINE` of either, and there can't be more phases
+    /// than disc counts.
+    ///
+    /// ```
+    /// # use othlib::eval::pattern::PatternWeights;
+    /// assert!(PatternWeights::check_size(8, 8, 4).is_ok());
+    /// assert!(PatternWeights::check_size(0, 0, 4).is_err());
+    /// assert!(PatternWeights::check_size(8, 26, 4).is_err());
+    /// assert!(PatternWeights::check_size(4, 4, 100).is_err());
+    /// ```
+    pub fn check_size(rows: usize, cols: usize, phases: usize) -> Result<(), String> {
+        if rows < 2 || cols < 2 {
+            Err(format!(
+                "Patterns need at least 2 rows and 2 columns, not {}x{}.",
+                rows, cols
+            ))
+        } else if rows.max(cols) > Self::MAX_LINE {
+            Err(format!(
+                "Patterns can only be kept for boards up to {} cells across, not {}x{}.",
+                Self::MAX_LINE,
+                rows,
+                cols
+            ))
+        } else if phases == 0 || phases > rows * cols + 1 {
+            Err(format!(
+                "A {}x{} board can have 1 to {} phases, not {}.",
+                rows,
+                cols,
+                rows * cols + 1,
+                phases
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Creates all-zero weights for a board size with `phases` game phases.
+    ///
+    /// The size should pass `check_size`: the tables grow exponentially with the length of the board's lines.
+    pub fn zeroed(rows: usize, cols: usize, phases: usize) -> Self {
+        let set = PatternSet::for_size(rows, cols);
+        let phases = phases.max(1);
+        let tables = (0..phases)
+            .map(|_| {
+                set.classes()
+                    .iter()
+                    .map(|class| vec![0.0; class.configurations()])
+                    .collect()
+            })
+            .collect();
+        Self {
+            set,
+            bias: vec![0.0; phases],
+            tables,
+        }
+    }
+
+    pub fn set(&self) -> &PatternSet {
+        &self.set
+    }
+
+    pub fn phases(&self) -> usize {
+        self.bias.len()
+    }
+
+    /// Maps a disc count onto a phase. Phases split the possible disc counts into equal ranges.
+    pub fn phase(&self, discs: usize) -> usize {
+        let cells = self.set.rows() * self.set.cols();
+        (discs * self.phases() / (cells + 1)).min(self.phases() - 1)
+    }
+
+    pub fn bias(&self, phase: usize) -> f32 {
+        self.bias[phase]
+    }
+
+    pub fn bias_mut(&mut self, phase: usize) -> &mut f32 {
+        &mut self.bias[phase]
+    }
+
+    pub fn weight(&self, phase: usize, class: usize, index: usize) -> f32 {
+        self.tables[phase][class][index]
+    }
+
+    pub fn weight_mut(&mut self, phase: usize, class: usize, index: usize) -> &mut f32 {
+        &mut self.tables[phase][class][index]
+    }
+
+    /// Returns the phase and features of a position, which is everything needed to score or train on it.
+    pub fn features(&self, board: &Board, symbol: char) -> (usize, Vec<(usize, usize)>) {
+        let discs = board.char_counts().values().sum();
+        (self.phase(discs), self.set.features(board, symbol))
+    }
+
+    /// Scores a position for `symbol` in (fractional) discs.
+    pub fn score(&self, board: &Board, symbol: char) -> f32 {
+        let (phase, features) = self.features(board, symbol);
+        features
+            .iter()
+            .fold(self.bias[phase], |score, &(class, index)| {
+                score + self.tables[phase][class][index]
+            })
+    }
+
+    /// Loads weights written by `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses the weight file format. Only non-zero weights are stored, one per line.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        if lines.next().map(str::trim) != Some(HEADER) {
+            return Err(invalid("Missing pattern weight header.".to_string()));
+        }
+
+        let mut weights: Option<Self> = None;
+        for line in lines {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let number = |index: usize| {
+                fields
+                    .get(index)
+                    .and_then(|field| field.parse::<usize>().ok())
+                    .ok_or_else(|| invalid(format!("Malformed line: {}", line)))
+            };
+            let value = |index: usize| {
+                fields
+                    .get(index)
+                    .and_then(|field| field.parse::<f32>().ok())
+                    .ok_or_else(|| invalid(format!("Malformed line: {}", line)))
+            };
+
+            match (fields[0], weights.as_mut()) {
+                ("size", None) => {
+                    let (rows, cols, phases) = (number(1)?, number(2)?, number(3)?);
+                    Self::check_size(rows, cols, phases).map_err(invalid)?;
+                    weights = Some(Self::zeroed(rows, cols, phases));
+                }
+                ("bias", Some(weights)) => {
+                    let phase = number(1)?;
+                    if phase >= weights.phases() {
+                        return Err(invalid(format!("Phase out of range: {}", line)));
+                    }
+                    weights.bias[phase] = value(2)?;
+                }
+                ("w", Some(weights)) => {
+                    let (phase, class, index) = (number(1)?, number(2)?, number(3)?);
+                    let cell = weights
+                        .tables
+                        .get_mut(phase)
+                        .and_then(|table| table.get_mut(class))
+                        .and_then(|table| table.get_mut(index))
+                        .ok_or_else(|| invalid(format!("Weight out of range: {}", line)))?;
+                    *cell = value(4)?;
+                }
+                _ => return Err(invalid(format!("Unexpected line: {}", line))),
+            }
+        }
+
+        weights.ok_or_else(|| invalid("Missing size line.".to_string()))
+    }
+
+    /// Writes the weights to a file `load` can read.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        writeln!(file, "{}", HEADER)?;
+        writeln!(
+            file,
+            "size {} {} {}",
+            self.set.rows(),
+            self.set.cols(),
+            self.phases()
+        )?;
+        for (phase, bias) in self.bias.iter().enumerate() {
+            writeln!(file, "bias {} {}", phase, bias)?;
+        }
+        for (phase, classes) in self.tables.iter().enumerate() {
+            for (class, table) in classes.iter().enumerate() {
+                for (index, weight) in table.iter().enumerate().filter(|(_, &w)| w != 0.0) {
+                    writeln!(file, "w {} {} {} {}", phase, class, index, weight)?;
+                }
+            }
+        }
+        file.flush()
+    }
+}
+
+/// Evaluates positions with trained pattern weights.
+///
+/// Boards that don't match the size the weights were trained for fall back to counting discs.
+pub struct PatternEval {
+    weights: PatternWeights,
+}
+
+impl PatternEval {
+    pub fn new(weights: PatternWeights) -> Self {
+        Self { weights }
+    }
+
+    /// Loads a weight file written by `PatternWeights::save`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        PatternWeights::load(path).map(Self::new)
+    }
+
+    pub fn weights(&self) -> &PatternWeights {
+        &self.weights
+    }
+}
+
+impl Evaluate for PatternEval {
+    fn evaluate(&self, game: &Othello, symbol: char) -> isize {
+        let board = game.board();
+        let set = self.weights.set();
+        if board.rows() != set.rows() || board.cols() != set.cols() {
+            return disc_differential(game, symbol);
+        }
+
+        self.weights.score(board, symbol).round() as isize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn weights_round_trip() {
+        let mut weights = PatternWeights::zeroed(4, 4, 3);
+        *weights.bias_mut(1) = 0.5;
+        *weights.weight_mut(2, 1, 7) = -1.25;
+
+        let path = std::env::temp_dir().join("othlib_weights_round_trip.txt");
+        weights.save(&path).unwrap();
+        let loaded = PatternWeights::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(3, loaded.phases());
+        assert_eq!(0.5, loaded.bias(1));
+        assert_eq!(-1.25, loaded.weight(2, 1, 7));
+        assert_eq!(0.0, loaded.weight(0, 1, 7));
+    }
+
+    #[test]
+    fn oversized_weights_are_errors() {
+        for size in &[
+            "size 0 0 4",
+            "size 1 8 4",
+            "size 30 30 1",
+            "size 8 8 0",
+            "size 4 4 1000000",
+        ] {
+            let text = format!("{}\n{}\n", HEADER, size);
+            let err = PatternWeights::parse(&text).unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidData, err.kind(), "{}", size);
+        }
+        assert!(PatternWeights::parse(&format!("{}\nsize 8 8 4\n", HEADER)).is_ok());
+    }
+
+    #[test]
+    fn features_cover_the_board() {
+        for &(rows, cols) in &[(4, 4), (6, 8), (2, 5)] {
+            let set = PatternSet::for_size(rows, cols);
+            for row in 0..rows {
+                for col in 0..cols {
+                    assert!(set
+                        .patterns()
+                        .iter()
+                        .any(|pattern| pattern.squares.contains(&(row, col))));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Fits pattern weights to labeled positions.
+//!
+//! Training data is CSV with a header row. The trainer reads the `board` column (see `Board::encode`),
+//! the `side` column (the symbol the label is for) and one numeric target column, which is usually the
+//! final disc differential or an exact solver score. Any other columns are ignored.
+
+use crate::board::Board;
+use crate::eval::pattern::PatternWeights;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A position with the score it's expected to have for `symbol`.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub board: Board,
+    pub symbol: char,
+    pub target: f32,
+}
+
+/// Reads labeled positions from a CSV file, taking labels from `target_column`.
+///
+/// Rows with an empty target are skipped, since solver scores are only filled in near the end of a game.
+pub fn load_samples<P: AsRef<Path>>(path: P, target_column: &str) -> io::Result<Vec<Sample>> {
+    parse_samples(&fs::read_to_string(path)?, target_column)
+}
+
+/// Parses labeled positions from CSV text. See `load_samples`.
+pub fn parse_samples(text: &str, target_column: &str) -> io::Result<Vec<Sample>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let header = lines
+        .next()
+        .ok_or_else(|| invalid("The training data is empty.".to_string()))?
+        .split(',')
+        .map(str::trim)
+        .collect::<Vec<_>>();
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|&column| column == name)
+            .ok_or_else(|| invalid(format!("Missing `{}` column.", name)))
+    };
+    let (board_column, side_column, target_column) =
+        (column("board")?, column("side")?, column(target_column)?);
+
+    let mut samples = vec![];
+    for line in lines {
+        let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+        let field = |index: usize| {
+            fields
+                .get(index)
+                .cloned()
+                .ok_or_else(|| invalid(format!("Too few columns: {}", line)))
+        };
+        let target = field(target_column)?;
+        if target.is_empty() {
+            continue;
+        }
+
+        samples.push(Sample {
+            board: Board::decode(field(board_column)?)
+                .ok_or_else(|| invalid(format!("Malformed board: {}", line)))?,
+            symbol: field(side_column)?
+                .chars()
+                .next()
+                .ok_or_else(|| invalid(format!("Missing side: {}", line)))?,
+            target: target
+                .parse()
+                .map_err(|_| invalid(format!("Malformed target: {}", line)))?,
+        });
+    }
+
+    Ok(samples)
+}
+
+/// Least-squares training by stochastic gradient descent.
+///
+/// Every sample activates one weight per pattern plus the phase bias. Each step moves those weights
+/// against the prediction error, and `regularization` pulls weights that are rarely seen back towards 0.
+#[derive(Copy, Clone, Debug)]
+pub struct Trainer {
+    pub epochs: usize,
+    pub learning_rate: f32,
+    pub regularization: f32,
+}
+
+impl Default for Trainer {
+    fn default() -> Self {
+        Self {
+            epochs: 20,
+            learning_rate: 0.1,
+            regularization: 0.0001,
+        }
+    }
+}
+
+impl Trainer {
+    /// Trains `weights` in place and returns the mean squared error of the last epoch.
+    ///
+    /// Samples for a different board size than the weights are skipped.
+    pub fn train(&self, weights: &mut PatternWeights, samples: &[Sample]) -> f32 {
+        let (rows, cols) = (weights.set().rows(), weights.set().cols());
+        let samples = samples
+            .iter()
+            .filter(|sample| sample.board.rows() == rows && sample.board.cols() == cols)
+            .map(|sample| {
+                let (phase, features) = weights.features(&sample.board, sample.symbol);
+                (phase, features, sample.target)
+            })
+            .collect::<Vec<_>>();
+        if samples.is_empty() {
+            return 0.0;
+        }
+
+        let mut error = 0.0;
+        for _ in 0..self.epochs {
+            error = 0.0;
+            for (phase, features, target) in &samples {
+                let prediction = features.iter().fold(weights.bias(*phase), |sum, &(c, i)| {
+                    sum + weights.weight(*phase, c, i)
+                });
+                let delta = prediction - target;
+                error += delta * delta;
+
+                // Spread the step across the active weights so the rate doesn't depend on the board size
+                let step = self.learning_rate * delta / (features.len() + 1) as f32;
+                *weights.bias_mut(*phase) -= step;
+                for &(class, index) in features {
+                    let weight = weights.weight_mut(*phase, class, index);
+                    *weight -= step + self.learning_rate * self.regularization * *weight;
+                }
+            }
+            error /= samples.len() as f32;
+        }
+
+        error
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn training_reduces_error() {
+        let data = "board,side,result\n\
+                    ..../.XO./.OX./....,X,0\n\
+                    ..../.XX./.XX./....,X,16\n\
+                    ..../.OO./.OO./....,X,-16\n\
+                    X.../.XO./.OX./...O,X,2\n";
+        let samples = parse_samples(data, "result").unwrap();
+        assert_eq!(4, samples.len());
+
+        let mut weights = PatternWeights::zeroed(4, 4, 2);
+        let short = Trainer {
+            epochs: 1,
+            ..Trainer::default()
+        }
+        .train(&mut weights.clone(), &samples);
+        let long = Trainer::default().train(&mut weights, &samples);
+        assert!(long < short);
+        assert!(weights.score(&samples[1].board, 'X') > weights.score(&samples[2].board, 'X'));
+    }
+
+    #[test]
+    fn empty_targets_are_skipped() {
+        let data = "board,side,result,score\n..../.XO./.OX./....,X,0,\n";
+        assert!(parse_samples(data, "score").unwrap().is_empty());
+        assert!(parse_samples(data, "missing").is_err());
+    }
+}
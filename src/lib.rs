@@ -94,18 +94,104 @@
 #![allow(dead_code)]
 #![deny(clippy::all)]
 
+pub mod analysis;
 pub mod board;
+pub mod cli;
+pub mod eval;
+pub mod nboard;
+pub mod net;
+pub mod openings;
 pub mod player;
+pub mod protocol;
+pub mod rng;
+pub mod rolit;
+pub mod rules;
+pub mod selfplay;
+pub mod service;
+pub mod sprt;
+pub mod svg;
+pub mod tournament;
+pub mod tui;
 
 use crate::board::*;
 use crate::player::Player;
+use crate::rules::{Captures, Grid, Layout, Setup, Topology, Variant};
 
 use std::fmt::{self, Write};
-use std::iter::Take;
+use std::iter::{self, Take};
+
+/// # Step
+///
+/// One step along a line of cells, as a change in row and column, and in layer on a cube. Every kind of grid has its
+/// own set of steps, see `Grid::steps`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Step {
+    pub layers: isize,
+    pub rows: isize,
+    pub cols: isize,
+}
+
+impl Step {
+    pub const fn new(rows: isize, cols: isize) -> Self {
+        Self::layered(0, rows, cols)
+    }
+
+    pub const fn layered(layers: isize, rows: isize, cols: isize) -> Self {
+        Self { layers, rows, cols }
+    }
+
+    /// The cell one step on from `row`, `col`. Returns `None` for underflowing values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use othlib::Step;
+    ///
+    /// assert_eq!(Some((2, 0)), Step::new(1, -1).apply(1, 1));
+    /// assert_eq!(None, Step::new(1, -1).apply(1, 0));
+    /// ```
+    pub fn apply(self, row: usize, col: usize) -> Option<(usize, usize)> {
+        Some((
+            row.checked_add_signed(self.rows)?,
+            col.checked_add_signed(self.cols)?,
+        ))
+    }
+
+    /// # Like `apply`, but on a cube `size` cells across
+    ///
+    /// A cube keeps its layers one above the other, `size` rows each, so row `row` is row `row % size` of layer
+    /// `row / size`. Returns `None` for steps off the bottom of the cube or out of the side of a layer. Steps off the
+    /// top or the far side are left to the bounds check, like with `apply`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use othlib::Step;
+    ///
+    /// // Up a layer and down a row, from row 0 of layer 1 on a 4x4x4 cube
+    /// assert_eq!(None, Step::layered(1, -1, 0).apply_in_cube(4, 0, 4));
+    /// assert_eq!(Some((8, 0)), Step::layered(1, -1, 0).apply_in_cube(5, 0, 4));
+    /// assert_eq!(Some((0, 1)), Step::layered(-1, 0, 1).apply_in_cube(4, 0, 4));
+    /// ```
+    pub fn apply_in_cube(self, row: usize, col: usize, size: usize) -> Option<(usize, usize)> {
+        let (layer, row) = (row / size, row % size);
+        let layer = layer.checked_add_signed(self.layers)?;
+        let row = Some(row.checked_add_signed(self.rows)?).filter(|&row| row < size)?;
+        Some((layer * size + row, col.checked_add_signed(self.cols)?))
+    }
+
+    /// Like `apply`, but on a `rows` by `cols` torus: stepping off one edge comes back on at the opposite edge.
+    pub fn wrapping(self, row: usize, col: usize, rows: usize, cols: usize) -> (usize, usize) {
+        (
+            (row as isize + self.rows).rem_euclid(rows as isize) as usize,
+            (col as isize + self.cols).rem_euclid(cols as isize) as usize,
+        )
+    }
+}
 
 /// # Direction
 ///
-/// Represents cardinal directions on the board.
+/// Represents cardinal directions on a square board.
 /// Combination directions represent diagonal movement.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Direction {
@@ -145,19 +231,46 @@ impl Direction {
     /// assert_eq!(Some((2, 0)), Direction::NW.new_coords_from_direction(1, 1));
     /// ````
     pub fn new_coords_from_direction(self, row: usize, col: usize) -> Option<(usize, usize)> {
+        self.step().apply(row, col)
+    }
+
+    /// The step this direction takes.
+    pub const fn step(self) -> Step {
         match self {
-            Direction::N => Some((row + 1, col)),
-            Direction::NE => Some((row + 1, col + 1)),
-            Direction::E => Some((row, col + 1)),
-            Direction::SE if row > 0 => Some((row - 1, col + 1)),
-            Direction::S if row > 0 => Some((row - 1, col)),
-            Direction::SW if row > 0 && col > 0 => Some((row - 1, col - 1)),
-            Direction::W if col > 0 => Some((row, col - 1)),
-            Direction::NW if col > 0 => Some((row + 1, col - 1)),
-            _ => None,
+            Direction::N => Step::new(1, 0),
+            Direction::NE => Step::new(1, 1),
+            Direction::E => Step::new(0, 1),
+            Direction::SE => Step::new(-1, 1),
+            Direction::S => Step::new(-1, 0),
+            Direction::SW => Step::new(-1, -1),
+            Direction::W => Step::new(0, -1),
+            Direction::NW => Step::new(1, -1),
         }
     }
 
+    /// Like `new_coords_from_direction`, but on a `rows` by `cols` torus: stepping off one edge comes back on at the
+    /// opposite edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use othlib::Direction;
+    ///
+    /// assert_eq!((3, 1), Direction::S.wrapping_coords(0, 1, 4, 6));
+    /// assert_eq!((0, 0), Direction::NE.wrapping_coords(3, 5, 4, 6));
+    /// assert_eq!((2, 5), Direction::W.wrapping_coords(2, 0, 4, 6));
+    /// assert_eq!((2, 2), Direction::N.wrapping_coords(1, 2, 4, 6));
+    /// ```
+    pub fn wrapping_coords(
+        self,
+        row: usize,
+        col: usize,
+        rows: usize,
+        cols: usize,
+    ) -> (usize, usize) {
+        self.step().wrapping(row, col, rows, cols)
+    }
+
     /// Loops through the cardinal directions once starting at north.
     ///
     /// # Examples
@@ -239,6 +352,19 @@ impl std::ops::Not for ActivePlayer {
     }
 }
 
+/// Where a game is with the swap rule.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Swap {
+    /// The game doesn't use it.
+    Off,
+    /// Nobody has moved yet. Discs placed while setting up don't count.
+    Waiting,
+    /// The first move has been played, and the second player can swap instead of moving.
+    Offered,
+    /// The second player swapped or moved.
+    Closed,
+}
+
 /// # Othello game implementation
 ///
 /// Accepts two different players and the starting player. Automatically initializes the board.
@@ -249,6 +375,13 @@ pub struct Othello<'a> {
     p_two: &'a dyn Player,
     active_player: ActivePlayer,
     board: Board,
+    forfeited: Option<ActivePlayer>,
+    variant: Variant,
+    topology: Topology,
+    setup: Setup,
+    grid: Grid,
+    captures: Captures,
+    swap: Swap,
 }
 
 impl<'a> Othello<'a> {
@@ -265,20 +398,276 @@ impl<'a> Othello<'a> {
     /// assert_eq!(Some('O'), game.board().get_cell(2, 1));
     /// assert_eq!(Some('O'), game.board().get_cell(1, 2));
     /// ```
-    pub fn with_players(p_one: &'a Player, p_two: &'a Player, rows: usize, cols: usize) -> Self {
+    ///
+    /// Boards with an odd number of rows and columns start with a cross around the empty center cell, see
+    /// `Grid::middle`.
+    ///
+    /// # Panics
+    ///
+    /// If the board is less than 2 cells across. `Rules::start` returns an error instead.
+    pub fn with_players(
+        p_one: &'a dyn Player,
+        p_two: &'a dyn Player,
+        rows: usize,
+        cols: usize,
+    ) -> Self {
+        if let Err(err) = Grid::Square.board_size(rows, cols) {
+            panic!("{}", err);
+        }
         let mut board = Board::with_size(rows, cols);
-        board.set_cell(rows / 2 - 1, cols / 2 - 1, p_one.get_symbol());
-        board.set_cell(rows / 2, cols / 2, p_one.get_symbol());
-        board.set_cell(rows / 2, cols / 2 - 1, p_two.get_symbol());
-        board.set_cell(rows / 2 - 1, cols / 2, p_two.get_symbol());
+        let symbols = [p_one.get_symbol(), p_two.get_symbol()];
+        for (index, (row, col)) in Grid::Square.middle(rows, cols).into_iter().enumerate() {
+            board.set_cell(row, col, symbols[Layout::Diagonal.owner(index)]);
+        }
         Self {
             p_one,
             p_two,
             board,
             active_player: ActivePlayer::PlayerOne,
+            forfeited: None,
+            variant: Variant::default(),
+            topology: Topology::default(),
+            setup: Setup::default(),
+            grid: Grid::default(),
+            captures: Captures::default(),
+            swap: Swap::Off,
         }
     }
 
+    /// # Resumes an Othello game from a position
+    ///
+    /// The board must only contain the two players' symbols.
+    /// ```
+    /// # use othlib::player::human::HumanPlayer;
+    /// # use othlib::board::Board;
+    /// # use othlib::{ActivePlayer, Othello};
+    /// let board = Board::decode("..../.XXX/.OX./....").unwrap();
+    /// let game = Othello::with_board(&HumanPlayer('X'), &HumanPlayer('O'), board, ActivePlayer::PlayerTwo);
+    /// assert_eq!('O', game.get_active_symbol());
+    /// assert_eq!(vec![(0, 1), (0, 3), (2, 3)], game.successors('O'));
+    /// ```
+    pub fn with_board(
+        p_one: &'a dyn Player,
+        p_two: &'a dyn Player,
+        board: Board,
+        active_player: ActivePlayer,
+    ) -> Self {
+        Self {
+            p_one,
+            p_two,
+            board,
+            active_player,
+            forfeited: None,
+            variant: Variant::default(),
+            topology: Topology::default(),
+            setup: Setup::default(),
+            grid: Grid::default(),
+            captures: Captures::default(),
+            swap: Swap::Off,
+        }
+    }
+
+    /// # Plays the game by a variant's rules
+    ///
+    /// ```
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::rules::Variant;
+    /// # use othlib::Othello;
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let mut game = Othello::with_players(&one, &two, 4, 4).with_variant(Variant::Misere);
+    /// game.run_silent();
+    /// let counts = game.board().char_counts();
+    /// let (x, o) = (counts.get(&'X').unwrap_or(&0), counts.get(&'O').unwrap_or(&0));
+    /// assert_eq!(if x < o { 1 } else if o < x { 2 } else { 0 }, game.get_winner_number());
+    /// ```
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// # Plays the game on a board of a different shape
+    ///
+    /// Fails if the mask is for another size or blocks a cell that has a disc on it.
+    ///
+    /// ```
+    /// # use othlib::board::Mask;
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::Othello;
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let mask = "..#./..../..../....".parse::<Mask>().unwrap();
+    /// let game = Othello::with_players(&one, &two, 4, 4).with_mask(mask).unwrap();
+    /// assert_eq!(vec![(1, 3), (2, 0), (3, 1)], game.successors('X'));
+    /// assert!(Othello::with_players(&one, &two, 4, 4).with_mask(Mask::full(6, 6)).is_err());
+    /// ```
+    pub fn with_mask(mut self, mask: Mask) -> Result<Self, String> {
+        self.board.apply_mask(mask)?;
+        Ok(self)
+    }
+
+    /// # Plays the game on a board whose edges wrap around
+    ///
+    /// ```
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::rules::Topology;
+    /// # use othlib::board::Board;
+    /// # use othlib::{ActivePlayer, Othello};
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let board = Board::decode("..../..../..../OX.O").unwrap();
+    /// let game = Othello::with_board(&one, &two, board, ActivePlayer::PlayerOne);
+    /// assert!(!game.is_legal_move(3, 2, 'X'));
+    /// // East of (3, 2) the line crosses the O at (3, 3), wraps around over the O at (3, 0) and ends at (3, 1)
+    /// let torus = game.with_topology(Topology::Torus);
+    /// assert_eq!(vec![(3, 0), (3, 3)], torus.flips(3, 2, 'X'));
+    /// ```
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// # Sets how the middle of the board is filled
+    ///
+    /// Under `Setup::Free`, a game whose middle squares aren't all full is still being set up: the only moves are
+    /// placing discs on the empty middle squares, and they don't flip anything. `Rules::start` begins those games
+    /// from an empty board.
+    ///
+    /// ```
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::rules::{Rules, Setup};
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let rules = Rules { setup: Setup::Free, ..Rules::default() };
+    /// let mut game = rules.start(&one, &two, 4, 4).unwrap();
+    /// assert_eq!(vec![(1, 1), (1, 2), (2, 1), (2, 2)], game.successors('X'));
+    /// game.try_move(1, 1).unwrap();
+    /// game.try_move(2, 2).unwrap();
+    /// game.try_move(1, 2).unwrap();
+    /// assert_eq!(vec![(2, 1)], game.successors('O'));
+    /// game.try_move(2, 1).unwrap();
+    /// assert!(game.is_set_up());
+    /// assert_eq!("..../.XX./.OO./....", game.board().encode());
+    /// ```
+    pub fn with_setup(mut self, setup: Setup) -> Self {
+        self.setup = setup;
+        self
+    }
+
+    pub fn setup(&self) -> Setup {
+        self.setup
+    }
+
+    /// # Plays the game on a different kind of grid
+    ///
+    /// The grid decides which way lines of discs run. `Rules::start` also gives a hex board its outline and starting
+    /// discs.
+    ///
+    /// ```
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::rules::{Grid, Rules};
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let rules = Rules { grid: Grid::Hex, ..Rules::default() };
+    /// let game = rules.start(&one, &two, 5, 5).unwrap();
+    /// assert_eq!(Grid::Hex, game.grid());
+    /// assert_eq!("##.../#.XO./.O.X./.XO.#/...##", game.board().encode());
+    /// assert_eq!(vec![(0, 3), (1, 1), (1, 4), (3, 0), (3, 3), (4, 1)], game.successors('X'));
+    /// ```
+    pub fn with_grid(mut self, grid: Grid) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    pub fn grid(&self) -> Grid {
+        self.grid
+    }
+
+    /// # Only captures along some lines
+    ///
+    /// Moves are legal only if they capture along one of those lines, and only those lines flip.
+    ///
+    /// ```
+    /// # use othlib::board::Board;
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::rules::Captures;
+    /// # use othlib::{ActivePlayer, Othello};
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let board = Board::decode("...../.OOO./.OXO./.OOO./.....").unwrap();
+    /// let game = Othello::with_board(&one, &two, board, ActivePlayer::PlayerOne);
+    /// let orthogonal = game.clone().with_captures(Captures::Orthogonal);
+    /// assert_eq!(vec![(0, 2), (2, 0), (2, 4), (4, 2)], orthogonal.successors('X'));
+    /// let diagonal = game.with_captures(Captures::Diagonal);
+    /// assert_eq!(vec![(0, 0), (0, 4), (4, 0), (4, 4)], diagonal.successors('X'));
+    /// assert_eq!(vec![(1, 1)], diagonal.flips(0, 0, 'X'));
+    /// ```
+    pub fn with_captures(mut self, captures: Captures) -> Self {
+        self.captures = captures;
+        self
+    }
+
+    pub fn captures(&self) -> Captures {
+        self.captures
+    }
+
+    /// The squares filled during a free setup.
+    fn middle(&self) -> Vec<(usize, usize)> {
+        self.grid.middle(self.board.rows(), self.board.cols())
+    }
+
+    /// Whether normal play has started: always under `Setup::Fixed`, and once the middle is full under
+    /// `Setup::Free`.
+    pub fn is_set_up(&self) -> bool {
+        self.setup == Setup::Fixed
+            || self.middle().iter().all(|&(row, col)| {
+                !self.board.is_in_bounds(row, col) || !self.board.is_cell_empty(row, col)
+            })
+    }
+
+    /// # Turns on the swap (pie) rule
+    ///
+    /// After the first move, the second player can take over the position the first player made instead of
+    /// moving. Symbols stay with their players, so swapping flips the color of every disc, and the first player
+    /// moves next. That gives the first player a reason to open with a move that's no better for either side.
+    ///
+    /// ```
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::{ActivePlayer, Othello};
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let mut game = Othello::with_players(&one, &two, 4, 4).with_swap_rule(true);
+    /// assert!(!game.can_swap());
+    /// game.try_move(0, 2).unwrap();
+    /// assert!(game.can_swap());
+    /// game.swap().unwrap();
+    /// assert_eq!("..O./.OO./.XO./....", game.board().encode());
+    /// assert_eq!(ActivePlayer::PlayerOne, game.active_player());
+    /// assert!(game.swap().is_err());
+    /// ```
+    pub fn with_swap_rule(mut self, on: bool) -> Self {
+        self.swap = if on { Swap::Waiting } else { Swap::Off };
+        self
+    }
+
+    /// Whether the player to move can swap instead of moving.
+    pub fn can_swap(&self) -> bool {
+        self.swap == Swap::Offered && self.forfeited.is_none()
+    }
+
+    /// Takes over the first player's position for the second player. See `with_swap_rule`.
+    pub fn swap(&mut self) -> Result<(), String> {
+        if !self.can_swap() {
+            return Err("Swapping is only allowed right after the first move.".to_string());
+        }
+        let (one, two) = (self.p_one.get_symbol(), self.p_two.get_symbol());
+        for row in 0..self.board.rows() {
+            for col in 0..self.board.cols() {
+                match self.board.get_cell(row, col) {
+                    Some(symbol) if symbol == one => self.board.set_cell(row, col, two),
+                    Some(symbol) if symbol == two => self.board.set_cell(row, col, one),
+                    _ => {}
+                }
+            }
+        }
+        self.swap = Swap::Closed;
+        self.change_active_player();
+        Ok(())
+    }
+
     pub fn active_as_num(&self) -> usize {
         match self.active_player() {
             ActivePlayer::PlayerOne => 1,
@@ -294,28 +683,54 @@ impl<'a> Othello<'a> {
         &self.board
     }
 
-    fn check_endpoint(
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// The cell one `step` on from `row`, `col`, if it's on the board.
+    fn step(&self, row: usize, col: usize, step: Step) -> Option<(usize, usize)> {
+        let next = match (self.grid, self.topology) {
+            (Grid::Cube, _) => step.apply_in_cube(row, col, self.board.cols())?,
+            (_, Topology::Flat) => step.apply(row, col)?,
+            (_, Topology::Torus) => step.wrapping(row, col, self.board.rows(), self.board.cols()),
+        };
+        Some(next).filter(|&(row, col)| self.board.is_in_bounds(row, col))
+    }
+
+    /// The cells a line from `row`, `col` crosses taking `step` after `step`, up to the edge of the board.
+    ///
+    /// On a torus a line has no edge and comes back around to where it started, so it stops just before that. A
+    /// line can't capture by wrapping around onto the disc that was just played.
+    fn line(
         &self,
         row: usize,
         col: usize,
-        symbol: char,
-        direction: Direction,
-        match_symbol: bool,
-    ) -> bool {
-        if !self.board.is_in_bounds(row, col) || self.board.is_cell_empty(row, col) {
-            return false;
-        }
+        step: Step,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        iter::successors(self.step(row, col, step), move |&(row, col)| {
+            self.step(row, col, step)
+        })
+        .take_while(move |&cell| cell != (row, col))
+    }
 
-        match self.board.get_cell(row, col) {
-            Some(symbol) if symbol == self.get_active_symbol() => match_symbol,
-            _ => {
-                if let Some((new_row, new_col)) = direction.new_coords_from_direction(row, col) {
-                    self.check_endpoint(new_row, new_col, symbol, direction, true)
-                } else {
-                    false
-                }
+    /// How many discs `symbol` playing at `row`, `col` would capture along the line `step` takes.
+    fn captured(&self, row: usize, col: usize, symbol: char, step: Step) -> usize {
+        // The same walk as `line`, written out because legality checks are most of what a search does
+        let mut captured = 0;
+        let mut next = self.step(row, col, step);
+        while let Some(cell) = next.filter(|&cell| cell != (row, col)) {
+            match self.board.get_cell(cell.0, cell.1) {
+                Some(disc) if disc == symbol => return captured,
+                Some(_) => captured += 1,
+                None => return 0,
             }
+            next = self.step(cell.0, cell.1, step);
         }
+        0
     }
 
     pub fn change_active_player(&mut self) {
@@ -324,30 +739,41 @@ impl<'a> Othello<'a> {
 
     pub fn flip_pieces(&mut self, row: usize, col: usize, symbol: char) -> usize {
         let mut flipped = 0;
-        //Loops through the cardinal directions once
-        for direction in Direction::cardinals() {
-            if let Some((new_row, new_col)) = direction.new_coords_from_direction(row, col) {
-                if self.check_endpoint(new_row, new_col, symbol, direction, false) {
-                    flipped += self.flip_helper(new_row, new_col, symbol, direction);
-                }
+        //Loops through the grid's directions once
+        for &step in self.captures.steps(self.grid) {
+            let captured = self.captured(row, col, symbol, step);
+            let cells = self.line(row, col, step).take(captured).collect::<Vec<_>>();
+            for (row, col) in cells {
+                self.board.set_cell(row, col, symbol);
             }
+            flipped += captured;
         }
 
         flipped
     }
 
-    fn flip_helper(&mut self, row: usize, col: usize, symbol: char, direction: Direction) -> usize {
-        match self.board.get_cell(row, col) {
-            Some(symbol) if symbol == self.get_active_symbol() => 0,
-            _ => {
-                self.board.set_cell(row, col, symbol);
-                if let Some((new_row, new_col)) = direction.new_coords_from_direction(row, col) {
-                    1 + self.flip_helper(new_row, new_col, symbol, direction)
-                } else {
-                    0
-                }
-            }
+    /// Lists the discs that `symbol` playing at `row`, `col` would flip, without playing the move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::Othello;
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let game = Othello::with_players(&one, &two, 4, 4);
+    /// assert_eq!(vec![(1, 2)], game.flips(0, 2, 'X'));
+    /// assert!(game.flips(0, 0, 'X').is_empty());
+    /// ```
+    pub fn flips(&self, row: usize, col: usize, symbol: char) -> Vec<(usize, usize)> {
+        if !self.is_legal_move(row, col, symbol) || !self.is_set_up() {
+            return vec![];
         }
+        let mut after = self.clone();
+        after.flip_pieces(row, col, symbol);
+        (0..self.board.rows())
+            .flat_map(|row| (0..self.board.cols()).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.board.get_cell(row, col) != after.board.get_cell(row, col))
+            .collect()
     }
 
     pub fn get_active_symbol(&self) -> char {
@@ -355,13 +781,35 @@ impl<'a> Othello<'a> {
     }
 
     pub fn get_move(&mut self) -> (usize, usize) {
-        match self.active_player {
-            ActivePlayer::PlayerOne => self.p_one.get_move(&self),
-            ActivePlayer::PlayerTwo => self.p_two.get_move(&self),
+        self.player(self.active_player).get_move(self)
+    }
+
+    fn player(&self, player: ActivePlayer) -> &'a dyn Player {
+        match player {
+            ActivePlayer::PlayerOne => self.p_one,
+            ActivePlayer::PlayerTwo => self.p_two,
         }
     }
 
+    /// Ends the game with `player` losing, no matter the score.
+    pub fn forfeit(&mut self, player: ActivePlayer) {
+        self.forfeited = Some(player);
+    }
+
+    /// The player who forfeited the game, if anyone did.
+    pub fn forfeited(&self) -> Option<ActivePlayer> {
+        self.forfeited
+    }
+
     pub fn get_winner(&self) -> Option<String> {
+        if let Some(player) = self.forfeited {
+            let winner = if player == ActivePlayer::PlayerOne {
+                2
+            } else {
+                1
+            };
+            return Some(format!("Player {} wins by forfeit!", winner));
+        }
         if self.has_more_moves() {
             return None;
         }
@@ -369,53 +817,87 @@ impl<'a> Othello<'a> {
         let char_map = self.board.char_counts();
         let p_one_count = char_map.get(&self.p_one.get_symbol()).unwrap_or(&0);
         let p_two_count = char_map.get(&self.p_two.get_symbol()).unwrap_or(&0);
-        if p_one_count > p_two_count {
-            Some(format!("Player 1 wins with {} points!", p_one_count))
-        } else if p_two_count > p_one_count {
-            Some(format!("Player 2 wins with {} points!", p_two_count))
-        } else {
-            Some("It's a tie!".to_string())
+        let (winner, count) = match self.get_winner_number() {
+            1 => (1, p_one_count),
+            2 => (2, p_two_count),
+            _ => return Some("It's a tie!".to_string()),
+        };
+        match self.variant {
+            Variant::Standard => Some(format!("Player {} wins with {} points!", winner, count)),
+            Variant::Misere => Some(format!("Player {} wins with only {} discs!", winner, count)),
         }
     }
 
     pub fn get_winner_number(&self) -> usize {
+        match self.forfeited {
+            Some(ActivePlayer::PlayerOne) => return 2,
+            Some(ActivePlayer::PlayerTwo) => return 1,
+            None => {}
+        }
+
         let char_map = self.board.char_counts();
-        let p_one_count = char_map.get(&self.p_one.get_symbol()).unwrap_or(&0);
-        let p_two_count = char_map.get(&self.p_two.get_symbol()).unwrap_or(&0);
-        if p_one_count > p_two_count {
-            1
-        } else if p_two_count > p_one_count {
-            2
-        } else {
-            0
+        let p_one_count = *char_map.get(&self.p_one.get_symbol()).unwrap_or(&0) as isize;
+        let p_two_count = *char_map.get(&self.p_two.get_symbol()).unwrap_or(&0) as isize;
+        match self.variant.outcome(p_one_count - p_two_count) {
+            x if x > 0 => 1,
+            x if x < 0 => 2,
+            _ => 0,
         }
     }
 
     pub fn has_more_moves(&self) -> bool {
-        self.player_has_more_moves(ActivePlayer::PlayerOne)
-            && self.player_has_more_moves(ActivePlayer::PlayerTwo)
+        self.forfeited.is_none()
+            && (self.player_has_more_moves(ActivePlayer::PlayerOne)
+                || self.player_has_more_moves(ActivePlayer::PlayerTwo))
     }
 
     pub fn is_legal_move(&self, row: usize, col: usize, symbol: char) -> bool {
         if !self.board.is_in_bounds(row, col) || !self.board.is_cell_empty(row, col) {
             return false;
         }
+        if !self.is_set_up() {
+            return self.middle().contains(&(row, col));
+        }
 
         //Loops through the cardinal directions once
-        Direction::cardinals().any(|direction| {
-            if let Some((new_row, new_col)) = direction.new_coords_from_direction(row, col) {
-                self.check_endpoint(new_row, new_col, symbol, direction, false)
-            } else {
-                false
-            }
-        })
+        self.captures
+            .steps(self.grid)
+            .iter()
+            .any(|&step| self.captured(row, col, symbol, step) > 0)
     }
 
+    /// Plays one turn, printing the board and the chosen move.
     pub fn next_turn(&mut self) -> bool {
+        self.take_turn(true)
+    }
+
+    /// Plays one turn without printing anything. Players may still print (humans need to be prompted).
+    pub fn play_turn(&mut self) -> bool {
+        self.take_turn(false)
+    }
+
+    fn take_turn(&mut self, verbose: bool) -> bool {
         let symbol = self.get_active_symbol();
 
-        println!("{}", self);
-        println!("Player {} ({}) move:", self.active_as_num(), symbol);
+        if verbose {
+            println!("{}", self);
+            if !self.is_set_up() {
+                println!("Setting up: place a disc on one of the empty middle squares.");
+            }
+            println!("Player {} ({}) move:", self.active_as_num(), symbol);
+        }
+
+        if self.can_swap() && self.player(self.active_player).wants_swap(self) {
+            if verbose {
+                println!(
+                    "Player {} ({}) swaps and takes over the first move.",
+                    self.active_as_num(),
+                    symbol
+                );
+            }
+            self.swap().expect("Swapping was just checked");
+            return true;
+        }
 
         let mut found_valid_move = false;
         while !found_valid_move
@@ -424,20 +906,39 @@ impl<'a> Othello<'a> {
         {
             let (row, col) = self.get_move();
             if !self.is_legal_move(row, col, symbol) {
-                println!("Invalid move.");
+                if self.player(self.active_player).forfeits_illegal_moves() {
+                    if verbose {
+                        println!(
+                            "Player {} ({}) forfeits with an invalid move.",
+                            self.active_as_num(),
+                            symbol
+                        );
+                    }
+                    self.forfeit(self.active_player);
+                    return false;
+                }
+                if verbose {
+                    println!("Invalid move.");
+                }
                 continue;
             }
-            println!("[Selected] Row: {}, Col: {}", row, col);
+            if verbose {
+                println!("[Selected] Row: {}, Col: {}", row, col);
+            }
             self.play_move(row, col, symbol);
             found_valid_move = true;
         }
 
         if !found_valid_move {
-            println!(
-                "Couldn't find valid move for Player {} ({})",
-                self.active_as_num(),
-                symbol
-            )
+            if verbose {
+                println!(
+                    "Couldn't find valid move for Player {} ({})",
+                    self.active_as_num(),
+                    symbol
+                );
+            }
+            // The turn passes to the opponent
+            self.change_active_player();
         }
         found_valid_move
     }
@@ -457,16 +958,70 @@ impl<'a> Othello<'a> {
     }
 
     pub fn play_move(&mut self, row: usize, col: usize, symbol: char) {
+        let setting_up = !self.is_set_up();
         self.board.set_cell(row, col, symbol);
-        self.flip_pieces(row, col, symbol);
-        self.change_active_player();
+        if !setting_up {
+            self.flip_pieces(row, col, symbol);
+        }
+        // Setup placements aren't moves, so the first move that can be swapped comes after them
+        if !setting_up {
+            self.swap = match self.swap {
+                Swap::Waiting => Swap::Offered,
+                Swap::Offered => Swap::Closed,
+                swap => swap,
+            };
+        }
+        // The mover's opponent is up next, even if the mover wasn't the active player
+        if let Some(player) = self.player_from_symbol(symbol) {
+            self.active_player = !player;
+        }
+    }
+
+    /// # Plays a move for the active player, refusing moves that aren't legal
+    ///
+    /// Unlike `play_move`, this can't put the game in a state the rules don't allow, so it's the one to use for moves
+    /// that come from outside the program.
+    ///
+    /// ```
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::{ActivePlayer, Othello};
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let mut game = Othello::with_players(&one, &two, 4, 4);
+    /// assert!(game.try_move(0, 0).is_err());
+    /// assert!(game.try_move(9, 9).is_err());
+    /// assert!(game.try_move(0, 2).is_ok());
+    /// assert_eq!(ActivePlayer::PlayerTwo, game.active_player());
+    /// ```
+    pub fn try_move(&mut self, row: usize, col: usize) -> Result<(), String> {
+        let symbol = self.get_active_symbol();
+        if !self.has_more_moves() {
+            return Err("The game is over.".to_string());
+        }
+        if !self.is_legal_move(row, col, symbol) {
+            return Err(format!(
+                "Row {}, Col {} isn't a legal move for {}.",
+                row, col, symbol
+            ));
+        }
+        self.play_move(row, col, symbol);
+        Ok(())
     }
 
+    /// Plays the game to the end, printing every turn, and returns the winner's number (0 for a tie).
     pub fn run(&mut self) -> usize {
+        self.run_with(true)
+    }
+
+    /// Plays the game to the end without printing every turn.
+    pub fn run_silent(&mut self) -> usize {
+        self.run_with(false)
+    }
+
+    fn run_with(&mut self, verbose: bool) -> usize {
         let mut iter_count = self.active_as_num() - 1;
         let mut played_successfully = [true, true];
         while self.has_more_moves() {
-            played_successfully[iter_count] = self.next_turn();
+            played_successfully[iter_count] = self.take_turn(verbose);
             if !played_successfully[0] && !played_successfully[1] {
                 break;
             }
@@ -519,34 +1074,50 @@ impl<'a> fmt::Display for Othello<'a> {
 
         let successors = self.successors(self.symbol_from_player(self.active_player()));
 
-        for row in (0..self.board().rows()).rev() {
-            write!(build, "{}:|", row)?;
+        // Hex rows shift half a cell right per row, so each column runs up and to the right
+        let hex = self.grid == Grid::Hex;
+        let shift = |row: usize| if hex { row } else { 0 };
+        // A cube is drawn a layer at a time from the top, with rows numbered the way they're stored
+        let rows = self.board().rows();
+        let layer = Some(self.board().cols()).filter(|_| self.grid == Grid::Cube);
+        let width = if layer.is_some() {
+            (rows - 1).to_string().len()
+        } else {
+            1
+        };
+        for row in (0..rows).rev() {
+            if let Some(size) = layer.filter(|size| row % size == size - 1) {
+                let gap = if row == rows - 1 { "" } else { "\n" };
+                writeln!(build, "{}Layer {}", gap, row / size)?;
+            }
+            let mut line = format!("{:>width$}:|{}", row, " ".repeat(shift(row)), width = width);
             for col in 0..self.board().cols() {
                 write!(
-                    build,
+                    line,
                     " {}",
-                    self.board().get_cell(row, col).unwrap_or_else(|| {
-                        if successors.contains(&(row, col)) {
-                            '?'
-                        } else {
-                            '.'
-                        }
-                    })
+                    match self.board().get_cell(row, col) {
+                        // The outline of a hex board is left out rather than drawn as walls
+                        _ if hex && self.board().is_blocked(row, col) => ' ',
+                        _ if self.board().is_blocked(row, col) => BLOCKED,
+                        Some(symbol) => symbol,
+                        None if successors.contains(&(row, col)) => '?',
+                        None => '.',
+                    }
                 )?;
             }
-            writeln!(build)?;
+            writeln!(build, "{}", line.trim_end())?;
         }
 
         writeln!(
             build,
-            "   {}",
-            std::iter::repeat("-")
-                .take((self.board().cols() * 2) + 1)
-                .collect::<String>()
+            "{}{}",
+            " ".repeat(width + 2),
+            "-".repeat((self.board().cols() * 2) + 1 + shift(rows - 1))
         )?;
         writeln!(
             build,
-            "    {}",
+            "{}{}",
+            " ".repeat(width + 3),
             (0..self.board().cols()).fold(String::new(), |mut accum, val| {
                 accum.push_str(&format!("{} ", val));
                 accum
@@ -577,12 +1148,8 @@ mod test {
         let iterations = 250;
         for i in 0..iterations {
             println!("Running Game: {} {} left", i, iterations - i);
-            let mut game = Othello::with_players(
-                &random::RandomPlayer('X'),
-                &minimax::MinimaxPlayer('O'),
-                4,
-                4,
-            );
+            let player_two = minimax::MinimaxPlayer::new('O');
+            let mut game = Othello::with_players(&random::RandomPlayer('X'), &player_two, 4, 4);
             game.run();
             assert_ne!(1, game.get_winner_number());
         }
@@ -590,13 +1157,323 @@ mod test {
 
     #[test]
     fn try_minimax() {
-        let mut game = Othello::with_players(
-            &minimax::MinimaxPlayer('X'),
-            &minimax::MinimaxPlayer('O'),
-            4,
-            4,
-        );
+        let player_one = minimax::MinimaxPlayer::new('X');
+        let player_two = minimax::MinimaxPlayer::new('O');
+        let mut game = Othello::with_players(&player_one, &player_two, 4, 4);
         game.run();
         assert_ne!(1, game.get_winner_number());
     }
+
+    #[test]
+    fn moves_are_checked_for_the_symbol_asked_about() {
+        use super::ActivePlayer;
+
+        let player_one = minimax::MinimaxPlayer::new('X');
+        let player_two = minimax::MinimaxPlayer::new('O');
+        let mut game = Othello::with_players(&player_one, &player_two, 4, 4);
+        // X is to move, which used to turn O's moves into X's
+        assert_eq!(vec![(0, 2), (1, 3), (2, 0), (3, 1)], game.successors('X'));
+        assert_eq!(vec![(0, 1), (1, 0), (2, 3), (3, 2)], game.successors('O'));
+
+        // O moving out of turn flips X's discs, and hands the turn to X
+        game.play_move(0, 1, 'O');
+        assert_eq!(Some(&4), game.board().char_counts().get(&'O'));
+        assert_eq!(ActivePlayer::PlayerOne, game.active_player());
+    }
+
+    #[test]
+    fn a_stuck_player_passes() {
+        use super::board::Board;
+        use super::ActivePlayer;
+
+        let player_one = minimax::MinimaxPlayer::new('X');
+        let player_two = minimax::MinimaxPlayer::new('O');
+        let mut game = Othello::with_players(&player_one, &player_two, 4, 4);
+        // X can't move but O can, which used to end the game
+        game.board = Board::with_size(4, 4);
+        game.board.set_cell(0, 0, 'O');
+        game.board.set_cell(0, 1, 'X');
+        assert!(game.has_more_moves());
+        assert!(!game.next_turn());
+        assert_eq!(ActivePlayer::PlayerTwo, game.active_player());
+        assert_eq!(2, game.run());
+        assert_eq!(Some(&3), game.board().char_counts().get(&'O'));
+    }
+
+    #[test]
+    fn misere_winner_has_fewer_discs() {
+        use super::board::Board;
+        use super::rules::Variant;
+        use super::ActivePlayer;
+
+        let player_one = minimax::MinimaxPlayer::new('X');
+        let player_two = minimax::MinimaxPlayer::new('O');
+        let board = Board::decode("XXXX/XXXX/XXOO/XXXX").unwrap();
+        let game = Othello::with_board(&player_one, &player_two, board, ActivePlayer::PlayerOne);
+        assert_eq!(1, game.get_winner_number());
+        assert_eq!(
+            Some("Player 1 wins with 14 points!".to_string()),
+            game.get_winner()
+        );
+
+        let game = game.with_variant(Variant::Misere);
+        assert_eq!(2, game.get_winner_number());
+        assert_eq!(
+            Some("Player 2 wins with only 2 discs!".to_string()),
+            game.get_winner()
+        );
+    }
+
+    #[test]
+    fn masked_cells_are_never_played() {
+        use super::board::Mask;
+
+        let player_one = minimax::MinimaxPlayer::new('X').with_depth(2);
+        let player_two = minimax::MinimaxPlayer::new('O').with_depth(2);
+        let mask = Mask::obstacles(6, 6, 8, 3);
+        let mut game = Othello::with_players(&player_one, &player_two, 6, 6)
+            .with_mask(mask.clone())
+            .unwrap();
+        game.run_silent();
+
+        assert!(!game.has_more_moves());
+        for row in 0..6 {
+            for col in 0..6 {
+                if mask.is_blocked(row, col) {
+                    assert_eq!(None, game.board().get_cell(row, col));
+                    assert!(game.try_move(row, col).is_err());
+                }
+            }
+        }
+        assert_eq!(
+            28,
+            game.board().char_counts().values().sum::<usize>() + game.board().empties()
+        );
+    }
+
+    #[test]
+    fn torus_lines_stop_where_they_started() {
+        use super::board::Board;
+        use super::rules::Topology;
+        use super::ActivePlayer;
+
+        let player_one = minimax::MinimaxPlayer::new('X');
+        let player_two = minimax::MinimaxPlayer::new('O');
+        // Row 0 wraps from (0, 3) back around to (0, 0), which must not count as the X that ends the line
+        let board = Board::decode(".OOO/O.../X.../....").unwrap();
+        let mut game =
+            Othello::with_board(&player_one, &player_two, board, ActivePlayer::PlayerOne)
+                .with_topology(Topology::Torus);
+        assert_eq!(vec![(1, 0)], game.flips(0, 0, 'X'));
+        game.try_move(0, 0).unwrap();
+        assert_eq!("XOOO/X.../X.../....", game.board().encode());
+
+        let mut game =
+            Othello::with_players(&player_one, &player_two, 4, 4).with_topology(Topology::Torus);
+        let score = player_one.search(&game).score;
+        game.run_silent();
+        assert!(!game.has_more_moves());
+        assert_eq!(score, super::eval::outcome(&game, 'X'));
+    }
+
+    #[test]
+    fn odd_boards_start_around_the_center() {
+        use super::rules::{Layout, Rules};
+
+        let player_one = minimax::MinimaxPlayer::new('X');
+        let player_two = minimax::MinimaxPlayer::new('O');
+        // Both sides odd: a cross around the empty center, so the first captures are diagonal
+        let game = Othello::with_players(&player_one, &player_two, 5, 5);
+        assert_eq!("...../..X../.O.O./..X../.....", game.board().encode());
+        assert_eq!(vec![(1, 0), (1, 4), (3, 0), (3, 4)], game.successors('X'));
+        // One side odd: the block covers the middle row and the one below it
+        let game = Othello::with_players(&player_one, &player_two, 5, 6);
+        assert_eq!("....../..XO../..OX../....../......", game.board().encode());
+
+        let rules = Rules {
+            layout: Layout::Parallel,
+            ..Rules::default()
+        };
+        let game = rules.start(&player_one, &player_two, 6, 6).unwrap();
+        assert_eq!(
+            "....../....../..XX../..OO../....../......",
+            game.board().encode()
+        );
+        assert_eq!(vec![(4, 1), (4, 2), (4, 3), (4, 4)], game.successors('X'));
+        assert_eq!(
+            Some("A board needs at least 2 rows and 2 columns, not 1x8.".to_string()),
+            Rules::default().start(&player_one, &player_two, 1, 8).err()
+        );
+    }
+
+    #[test]
+    fn hex_lines_run_six_ways() {
+        use super::board::Board;
+        use super::rules::{Grid, Rules};
+        use super::ActivePlayer;
+
+        let player_one = minimax::MinimaxPlayer::new('X');
+        let player_two = minimax::MinimaxPlayer::new('O');
+        // (0, 2) to (2, 4) is a square diagonal but not a hex line, while (0, 2) to (2, 0) is a hex line
+        let board = Board::decode("##.../#O.O./X.O.X/....#/...##").unwrap();
+        let game = Othello::with_board(&player_one, &player_two, board, ActivePlayer::PlayerOne)
+            .with_grid(Grid::Hex);
+        assert_eq!(vec![(1, 1)], game.flips(0, 2, 'X'));
+
+        let rules = Rules {
+            grid: Grid::Hex,
+            ..Rules::default()
+        };
+        let mut game = rules.start(&player_one, &player_two, 5, 5).unwrap();
+        // Rows shift right as they go up, and the outline isn't drawn
+        assert!(game
+            .to_string()
+            .contains("\n4:|     . ? .\n3:|    ? X O ?\n2:|   . O . X .\n"));
+        let score = player_one.search(&game).score;
+        game.run_silent();
+        assert!(!game.has_more_moves());
+        assert_eq!(score, super::eval::outcome(&game, 'X'));
+    }
+
+    #[test]
+    fn cube_lines_run_through_layers() {
+        use super::board::Board;
+        use super::rules::{Grid, Rules};
+        use super::ActivePlayer;
+
+        let player_one = minimax::MinimaxPlayer::new('X').with_depth(1);
+        let player_two = minimax::MinimaxPlayer::new('O').with_depth(1);
+        // X at (0, 0, 0) and O at (1, 1, 1), so X playing (2, 2, 2) flips along the long diagonal
+        let mut board = Board::with_size(16, 4);
+        board.set_cell(0, 0, 'X');
+        board.set_cell(5, 1, 'O');
+        let game = Othello::with_board(&player_one, &player_two, board, ActivePlayer::PlayerOne)
+            .with_grid(Grid::Cube);
+        assert_eq!(vec![(10, 2)], game.successors('X'));
+        assert_eq!(vec![(5, 1)], game.flips(10, 2, 'X'));
+        // Row 3 of layer 0 and row 0 of layer 1 are next to each other on the board, but not in the cube
+        let board = Board::decode(
+            "..../..../..../.O../X.../..../..../..../..../..../..../..../..../..../..../....",
+        );
+        let game = Othello::with_board(
+            &player_one,
+            &player_two,
+            board.unwrap(),
+            ActivePlayer::PlayerOne,
+        )
+        .with_grid(Grid::Cube);
+        assert!(game.successors('X').is_empty());
+
+        let rules = Rules {
+            grid: Grid::Cube,
+            ..Rules::default()
+        };
+        let mut game = rules.start(&player_one, &player_two, 4, 4).unwrap();
+        assert_eq!((16, 4), (game.board().rows(), game.board().cols()));
+        assert!(game.to_string().contains("Layer 3\n15:| . . . ?\n"));
+        game.run_silent();
+        assert!(!game.has_more_moves());
+    }
+
+    #[test]
+    fn searches_keep_to_the_capture_preset() {
+        use super::board::Board;
+        use super::rules::Captures;
+        use super::ActivePlayer;
+
+        let player_one = minimax::MinimaxPlayer::new('X');
+        let player_two = minimax::MinimaxPlayer::new('O');
+        // (0, 0) only captures along the diagonal to (2, 2)
+        let board = Board::decode("...O/.OOX/.OX./X...").unwrap();
+        let game = Othello::with_board(&player_one, &player_two, board, ActivePlayer::PlayerOne);
+        let orthogonal = game.clone().with_captures(Captures::Orthogonal);
+        assert!(game.is_legal_move(0, 0, 'X'));
+        assert!(!orthogonal.is_legal_move(0, 0, 'X'));
+
+        for mut game in [game, orthogonal] {
+            let info = player_one.search(&game);
+            let (row, col) = info.best_move.unwrap();
+            assert!(game.is_legal_move(row, col, 'X'));
+            game.run_silent();
+            assert_eq!(info.score, super::eval::outcome(&game, 'X'));
+        }
+    }
+
+    #[test]
+    fn free_setup_fills_the_middle_first() {
+        use super::rules::{Rules, Setup};
+
+        let player_one = minimax::MinimaxPlayer::new('X').with_depth(3);
+        let player_two = minimax::MinimaxPlayer::new('O').with_depth(3);
+        let rules = Rules {
+            setup: Setup::Free,
+            ..Rules::default()
+        };
+        let mut game = rules.start(&player_one, &player_two, 6, 6).unwrap();
+        for _ in 0..4 {
+            assert!(!game.is_set_up());
+            assert!(game.flips(2, 2, 'X').is_empty());
+            game.play_turn();
+        }
+        assert!(game.is_set_up());
+        let counts = game.board().char_counts();
+        assert_eq!((Some(&2), Some(&2)), (counts.get(&'X'), counts.get(&'O')));
+        for (row, col) in [(2, 2), (2, 3), (3, 2), (3, 3)] {
+            assert!(!game.board().is_cell_empty(row, col));
+        }
+
+        game.run_silent();
+        assert!(!game.has_more_moves());
+    }
+
+    #[test]
+    fn free_setup_offers_the_swap_after_the_first_move() {
+        use super::rules::{Rules, Setup};
+
+        let player_one = minimax::MinimaxPlayer::new('X');
+        let player_two = minimax::MinimaxPlayer::new('O');
+        let rules = Rules {
+            setup: Setup::Free,
+            swap: true,
+            ..Rules::default()
+        };
+        let mut game = rules.start(&player_one, &player_two, 4, 4).unwrap();
+        for (row, col) in [(1, 1), (1, 2), (2, 2), (2, 1)] {
+            assert!(!game.can_swap());
+            game.try_move(row, col).unwrap();
+        }
+        assert!(game.is_set_up());
+        assert!(!game.can_swap());
+
+        let (row, col) = game.successors('X')[0];
+        game.try_move(row, col).unwrap();
+        assert!(game.can_swap());
+        game.swap().unwrap();
+        assert_eq!(super::ActivePlayer::PlayerOne, game.active_player());
+        assert!(!game.can_swap());
+    }
+
+    #[test]
+    fn try_depth_limited_patterns() {
+        use super::eval::pattern::{PatternEval, PatternKind, PatternWeights};
+
+        let mut weights = PatternWeights::zeroed(6, 6, 4);
+        let corner = weights
+            .set()
+            .classes()
+            .iter()
+            .position(|class| class.kind == PatternKind::Corner)
+            .unwrap();
+        for phase in 0..4 {
+            // Favour owning the corner square itself, which is read first
+            *weights.weight_mut(phase, corner, 3usize.pow(8)) = 4.0;
+        }
+        let player_one = minimax::MinimaxPlayer::new('X')
+            .with_depth(2)
+            .with_evaluator(PatternEval::new(weights));
+        let player_two = minimax::MinimaxPlayer::new('O').with_depth(1);
+        let mut game = Othello::with_players(&player_one, &player_two, 6, 6);
+        game.run();
+        assert!(!game.has_more_moves());
+    }
 }
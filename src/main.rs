@@ -1,65 +1,59 @@
 #![warn(clippy::all)]
 
-use othlib::player::{human::*, minimax::*, Player};
+mod commands;
 
-#[cfg(feature = "with_random")]
-use othlib::player::random::*;
+use commands::{analysis, bots, engine, game, render, train};
 
 use std::env::args;
 
-fn main() {
-    let args = args().skip(1).take(4).collect::<Vec<_>>();
-    if args.len() < 2 {
-        eprintln!("Usage: miniothello <player type> <player type>");
-        std::process::exit(1);
+/// Turns the original `othello <player> <player> [rows] [cols]` form into arguments for `play`.
+fn legacy_play_args(args: &[String]) -> Vec<String> {
+    let (flags, positionals): (Vec<_>, Vec<_>) =
+        args.iter().cloned().partition(|x| x.starts_with("--"));
+    let mut play_args = flags;
+    for (option, value) in ["--rows", "--cols"].iter().zip(positionals.iter().skip(2)) {
+        play_args.push(option.to_string());
+        play_args.push(value.clone());
+    }
+    // Boards were 4x4 unless a size was given
+    for option in ["--rows", "--cols"]
+        .iter()
+        .skip(positionals.len().saturating_sub(2))
+    {
+        play_args.push(option.to_string());
+        play_args.push("4".to_string());
     }
+    play_args.extend(positionals.into_iter().take(2));
+    play_args
+}
 
-    let player_one: &dyn Player = match args[0].as_ref() {
-        "human" => &HumanPlayer('X'),
-        #[cfg(feature = "with_random")]
-        "random" => &RandomPlayer('X'),
-        "minimax" => &MinimaxPlayer('X'),
-        _ => {
-            #[cfg(feature = "with_random")]
-            eprintln!("Possible player types are `human`, `random`, and `minimax`");
-            #[cfg(not(feature = "with_random"))]
-            eprintln!("Possible player types are `human` and `minimax`");
-            std::process::exit(3);
+fn main() {
+    let args = args().skip(1).collect::<Vec<_>>();
+    let rest = args.get(1..).unwrap_or(&[]);
+    match args.first().map(String::as_str) {
+        Some("play") => game::play(rest),
+        Some("rolit") => game::rolit(rest),
+        Some("analyze") => analysis::analyze(rest),
+        Some("solve") => analysis::solve(rest),
+        Some("selfplay") => bots::selfplay(rest),
+        Some("tournament") => bots::tournament(rest),
+        Some("sprt") => bots::sprt(rest),
+        Some("openings") => bots::openings(rest),
+        Some("render") => render::render(rest),
+        Some("train") => train::train(rest),
+        Some("engine") => engine::engine(rest),
+        Some("host") => game::host(rest),
+        Some("join") => game::join(rest),
+        Some("serve") => engine::serve(rest),
+        Some("help") | Some("--help") | Some("-h") => print!("{}", commands::help()),
+        None => {
+            eprint!("{}", commands::help());
+            std::process::exit(1);
         }
-    };
-
-    let player_two: &dyn Player = match args[1].as_ref() {
-        "human" => &HumanPlayer('O'),
-        #[cfg(feature = "with_random")]
-        "random" => &RandomPlayer('O'),
-        "minimax" => &MinimaxPlayer('O'),
-        _ => {
-            #[cfg(feature = "with_random")]
-            eprintln!("Possible player types are `human`, `random`, and `minimax`");
-            #[cfg(not(feature = "with_random"))]
-            eprintln!("Possible player types are `human` and `minimax`");
-            std::process::exit(3);
+        Some(_) if args.iter().filter(|x| !x.starts_with("--")).count() < 2 => {
+            eprint!("{}", commands::help());
+            std::process::exit(1);
         }
-    };
-
-    let mut game = othlib::Othello::with_players(
-        player_one,
-        player_two,
-        args.get(2)
-            .and_then(|x| x.parse::<usize>().ok())
-            .unwrap_or(4),
-        args.get(3)
-            .and_then(|x| x.parse::<usize>().ok())
-            .unwrap_or(4),
-    );
-
-    game.run();
-
-    println!(
-        "\n================================\n{}\n================================\n\nBoard: \n{}",
-        game.get_winner().unwrap_or_else(|| {
-            "The game ended with more moves left. I'm not sure how this happened.".to_string()
-        }),
-        game
-    );
+        Some(_) => game::play(&legacy_play_args(&args)),
+    }
 }
@@ -0,0 +1,434 @@
+//! An engine that speaks the NBoard protocol, so othlib players can be driven by NBoard and similar GUIs.
+//!
+//! The GUI sends one command per line on stdin and the engine answers on stdout. The supported commands are:
+//!
+//! * `nboard <version>`: answered with `set myname othlib`
+//! * `set depth <n>`: limits the search depth of minimax players
+//! * `set game <ggf>`: replaces the game with a GGF game record
+//! * `move <move>[/<eval>[/<time>]]`: plays a move, `PA` is a pass
+//! * `hint <n>`: sends a `search` line for each of the best `n` moves
+//! * `go`: answered with `=== <move>`, the move the configured player chose
+//! * `ping <n>`: answered with `pong <n>`
+//! * `quit`
+//!
+//! Anything else (like `set contempt` or `learn`) is accepted and ignored. Black is player one and plays `X`.
+//!
+//! Squares are written column letter first, then the row number counted from the top of the board, so `a1` is the
+//! top left. othlib counts rows from the bottom, which means `a1` on an 8x8 board is row 7, column 0.
+
+use crate::analysis::analyze_with;
+use crate::board::Board;
+use crate::player::spec::PlayerSpec;
+use crate::player::Player;
+use crate::{ActivePlayer, Othello};
+
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
+
+type Players = (Box<dyn Player>, Box<dyn Player>);
+
+const BLACK: char = 'X';
+const WHITE: char = 'O';
+
+/// The board sizes a GGF record can have, up to the last column `format_square` has a letter for.
+const SIZES: RangeInclusive<usize> = 4..=26;
+
+/// Formats a square in NBoard's notation for a board with `rows` rows.
+///
+/// # Examples
+///
+/// ```
+/// # use othlib::nboard::{format_square, parse_square};
+/// assert_eq!("d5", format_square((3, 3), 8));
+/// assert_eq!(Some((3, 3)), parse_square("D5", 8, 8));
+/// assert_eq!(None, parse_square("i1", 8, 8));
+/// ```
+pub fn format_square((row, col): (usize, usize), rows: usize) -> String {
+    format!("{}{}", (b'a' + col as u8) as char, rows - row)
+}
+
+/// Parses a square in NBoard's notation. Returns `None` for passes and squares off the board.
+pub fn parse_square(text: &str, rows: usize, cols: usize) -> Option<(usize, usize)> {
+    let text = text.trim().to_ascii_lowercase();
+    let mut chars = text.chars();
+    let col = chars.next().filter(char::is_ascii_lowercase)? as usize - 'a' as usize;
+    let row = chars.as_str().parse::<usize>().ok()?;
+    if col >= cols || row == 0 || row > rows {
+        return None;
+    }
+    Some((rows - row, col))
+}
+
+/// The parts of a GGF game record the engine needs.
+#[derive(Clone, Debug)]
+pub struct GgfGame {
+    /// The starting position.
+    pub board: Board,
+    /// Who moves first from `board`.
+    pub active: ActivePlayer,
+    /// The moves played from the starting position. `None` is a pass.
+    pub moves: Vec<Option<(usize, usize)>>,
+}
+
+/// Reads the board, side to move and moves out of a GGF game record.
+///
+/// Fails on a malformed record, or a board less than 4 or more than 26 squares across.
+///
+/// # Examples
+///
+/// ```
+/// # use othlib::nboard::parse_ggf;
+/// # use othlib::ActivePlayer;
+/// let ggf = "(;GM[Othello]PC[NBoard]TY[8]BO[8 ---------------------------O*------*O--------------------------- *]B[F5//1.2]W[d6];)";
+/// let game = parse_ggf(ggf).unwrap();
+/// assert_eq!(Some('O'), game.board.get_cell(4, 3));
+/// assert_eq!(ActivePlayer::PlayerOne, game.active);
+/// assert_eq!(vec![Some((3, 5)), Some((2, 3))], game.moves);
+/// assert!(parse_ggf("(;GM[Othello]BO[8];)").is_err());
+/// ```
+pub fn parse_ggf(ggf: &str) -> Result<GgfGame, String> {
+    let mut board = None;
+    let mut active = ActivePlayer::PlayerOne;
+    let mut moves = vec![];
+
+    let mut rest = ggf;
+    while let Some(open) = rest.find('[') {
+        let close = rest[open..]
+            .find(']')
+            .map(|close| open + close)
+            .ok_or_else(|| "Unclosed GGF tag.".to_string())?;
+        let tag = rest[..open]
+            .trim_start_matches(|x: char| !x.is_ascii_alphabetic())
+            .trim();
+        let value = &rest[open + 1..close];
+        rest = &rest[close + 1..];
+
+        match tag {
+            "BO" => {
+                let fields = value.split_whitespace().collect::<Vec<_>>();
+                if fields.len() < 3 {
+                    return Err(format!(
+                        "BO[{}] needs a size, the squares and the side to move",
+                        value
+                    ));
+                }
+                let size = fields[0]
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|size| SIZES.contains(size))
+                    .ok_or_else(|| format!("Bad board size in BO[{}]", value))?;
+                let cells = fields[1..fields.len().saturating_sub(1)].concat();
+                if cells.chars().count() != size * size {
+                    return Err(format!("Expected {} squares in BO[{}]", size * size, value));
+                }
+
+                let mut parsed = Board::with_size(size, size);
+                for (index, cell) in cells.chars().enumerate() {
+                    let (row, col) = (size - 1 - index / size, index % size);
+                    match cell {
+                        '*' | 'x' | 'X' | '#' => parsed.set_cell(row, col, BLACK),
+                        'O' | 'o' => parsed.set_cell(row, col, WHITE),
+                        _ => {}
+                    }
+                }
+                active = match fields.last() {
+                    Some(&"O") | Some(&"o") => ActivePlayer::PlayerTwo,
+                    _ => ActivePlayer::PlayerOne,
+                };
+                board = Some(parsed);
+            }
+            "B" | "W" => {
+                let size = board
+                    .as_ref()
+                    .map(Board::rows)
+                    .ok_or_else(|| "A move came before the board.".to_string())?;
+                let square = value.split('/').next().unwrap_or("");
+                let coords = parse_square(square, size, size);
+                if coords.is_none() && !square.eq_ignore_ascii_case("pa") {
+                    return Err(format!("Bad move {}[{}]", tag, value));
+                }
+                moves.push(coords);
+            }
+            _ => {}
+        }
+    }
+
+    board
+        .map(|board| GgfGame {
+            board,
+            active,
+            moves,
+        })
+        .ok_or_else(|| "The game has no BO[] tag.".to_string())
+}
+
+/// # NBoard engine state
+///
+/// Keeps the current position and builds the configured player whenever a move is needed.
+pub struct NBoardEngine {
+    spec: PlayerSpec,
+    board: Board,
+    active: ActivePlayer,
+}
+
+impl NBoardEngine {
+    /// Creates an engine playing as `spec`. Human players can't be engines.
+    pub fn new(spec: PlayerSpec) -> Result<Self, String> {
+        if !spec.kind.is_bot() {
+            return Err("The engine needs a bot to play.".to_string());
+        }
+        // Building the players up front also makes sure the spec works (e.g. its weight file exists)
+        let (one, two) = (spec.build(BLACK)?, spec.build(WHITE)?);
+        let board = Othello::with_players(&*one, &*two, 8, 8).board().clone();
+        Ok(Self {
+            spec,
+            board,
+            active: ActivePlayer::PlayerOne,
+        })
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn active_player(&self) -> ActivePlayer {
+        self.active
+    }
+
+    /// Plays a move (or a pass, `None`) on the current position.
+    fn play(&mut self, coords: Option<(usize, usize)>) -> Result<(), String> {
+        let (one, two) = self.players()?;
+        let mut game = Othello::with_board(&*one, &*two, self.board.clone(), self.active);
+        let symbol = game.get_active_symbol();
+        match coords {
+            Some((row, col)) if game.is_legal_move(row, col, symbol) => {
+                game.play_move(row, col, symbol)
+            }
+            None if !game.player_has_more_moves(self.active) => game.change_active_player(),
+            None => return Err("Illegal move PA".to_string()),
+            Some(coords) => {
+                return Err(format!(
+                    "Illegal move {}",
+                    format_square(coords, self.board.rows())
+                ))
+            }
+        }
+        self.board = game.board().clone();
+        self.active = game.active_player();
+        Ok(())
+    }
+
+    fn players(&self) -> Result<Players, String> {
+        Ok((self.spec.build(BLACK)?, self.spec.build(WHITE)?))
+    }
+
+    /// Handles one line of input. Returns `false` once the GUI asks the engine to quit.
+    pub fn handle<W: Write>(&mut self, line: &str, out: &mut W) -> io::Result<bool> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let argument = words.next().unwrap_or("");
+
+        match command {
+            "nboard" => writeln!(out, "set myname othlib")?,
+            "ping" => writeln!(out, "pong {}", argument)?,
+            "quit" => return Ok(false),
+            "set" if argument == "depth" => {
+                if let Some(depth) = words.next().and_then(|x| x.parse::<usize>().ok()) {
+                    self.spec.depth = Some(depth.max(1));
+                }
+            }
+            "set" if argument == "game" => {
+                let ggf = line.splitn(3, char::is_whitespace).nth(2).unwrap_or("");
+                match self.set_game(ggf) {
+                    Ok(()) => {}
+                    Err(err) => writeln!(out, "status {}", err)?,
+                }
+            }
+            "move" => {
+                let square = argument.split('/').next().unwrap_or("");
+                let rows = self.board.rows();
+                let played = match parse_square(square, rows, self.board.cols()) {
+                    Some(coords) => self.play(Some(coords)),
+                    None if square.eq_ignore_ascii_case("pa") => self.play(None),
+                    None => Err(format!("Bad move {}", argument)),
+                };
+                if let Err(err) = played {
+                    writeln!(out, "status {}", err)?;
+                }
+            }
+            "go" => {
+                let chosen = self.choose_move().unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    None
+                });
+                match chosen {
+                    Some(coords) => {
+                        writeln!(out, "=== {}", format_square(coords, self.board.rows()))?
+                    }
+                    None => writeln!(out, "=== PA")?,
+                }
+            }
+            "hint" => {
+                let count = argument.parse::<usize>().unwrap_or(1);
+                writeln!(out, "status Analyzing")?;
+                for (pv, score, depth) in self.hints(count) {
+                    writeln!(out, "search {} {} 0 {}", pv, score, depth)?;
+                }
+                writeln!(out, "status")?;
+            }
+            _ => {}
+        }
+
+        out.flush()?;
+        Ok(true)
+    }
+
+    /// Replaces the position with the one the record ends on, or leaves it alone if the record is bad.
+    fn set_game(&mut self, ggf: &str) -> Result<(), String> {
+        let game = parse_ggf(ggf)?;
+        let mut next = Self {
+            spec: self.spec.clone(),
+            board: game.board,
+            active: game.active,
+        };
+        for coords in game.moves {
+            next.play(coords)?;
+        }
+        *self = next;
+        Ok(())
+    }
+
+    /// Asks the configured player for a move. Returns `None` if the side to move has to pass.
+    fn choose_move(&self) -> Result<Option<(usize, usize)>, String> {
+        let (one, two) = self.players()?;
+        let mut game = Othello::with_board(&*one, &*two, self.board.clone(), self.active);
+        if !game.player_has_more_moves(self.active) {
+            return Ok(None);
+        }
+        Ok(Some(game.get_move()))
+    }
+
+    /// Returns the principal variation, score and depth of the best `count` moves.
+    fn hints(&self, count: usize) -> Vec<(String, isize, usize)> {
+        let (one, two) = match self.players() {
+            Ok(players) => players,
+            Err(_) => return vec![],
+        };
+        let game = Othello::with_board(&*one, &*two, self.board.clone(), self.active);
+        let limits = self
+            .spec
+            .build_minimax(game.get_active_symbol())
+            .map(|player| player.limits())
+            .unwrap_or_default();
+        let rows = self.board.rows();
+
+        analyze_with(&game, limits, |symbol| {
+            self.spec
+                .build_minimax(symbol)
+                .expect("The spec was checked when the engine was created.")
+        })
+        .into_iter()
+        .take(count)
+        .map(|analysis| {
+            let pv = analysis
+                .pv
+                .iter()
+                .map(|step| step.map_or("PA".to_string(), |coords| format_square(coords, rows)))
+                .collect::<String>();
+            (pv, analysis.score, analysis.depth)
+        })
+        .collect()
+    }
+
+    /// Answers commands from `input` until it ends or the GUI sends `quit`.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut out: W) -> io::Result<()> {
+        for line in input.lines() {
+            if !self.handle(&line?, &mut out)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn engine_output(commands: &str) -> String {
+        let spec = "minimax:depth=2".parse::<PlayerSpec>().unwrap();
+        let mut engine = NBoardEngine::new(spec).unwrap();
+        let mut out = vec![];
+        engine.run(commands.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn answers_go_with_a_legal_move() {
+        let output = engine_output("nboard 2\nset depth 2\nmove f5\nping 1\ngo\n");
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!("set myname othlib", lines[0]);
+        assert_eq!("pong 1", lines[1]);
+        // After f5, white's legal replies are d6, f4 and f6
+        assert!(["=== d6", "=== f4", "=== f6"].contains(&lines[2]));
+    }
+
+    #[test]
+    fn rejects_passes_with_moves_left() {
+        let output = engine_output("move PA\nhint 5\n");
+        assert!(output.contains("status Illegal move PA"));
+        // Black is still to move, with d3, c4, f5 and e6
+        let searches = output
+            .lines()
+            .filter(|line| line.starts_with("search"))
+            .collect::<Vec<_>>();
+        assert_eq!(4, searches.len());
+        // Each hint was searched to the depth the engine was set to
+        assert!(searches.iter().all(|line| line.ends_with(" 0 2")));
+    }
+
+    #[test]
+    fn set_game_replays_moves() {
+        let ggf = "(;GM[Othello]PC[NBoard]TY[8]BO[8 ---------------------------O*------*O--------------------------- *]B[F5]W[F6]B[E6];)";
+        let output = engine_output(&format!("set game {}\nhint 5\n", ggf));
+        let searches = output
+            .lines()
+            .filter(|line| line.starts_with("search"))
+            .collect::<Vec<_>>();
+        // White can only answer with d6 or f4
+        assert_eq!(2, searches.len());
+        assert!(searches
+            .iter()
+            .all(|line| line.starts_with("search d6") || line.starts_with("search f4")));
+        assert!(!output.contains("Illegal"));
+    }
+
+    #[test]
+    fn rejects_malformed_games() {
+        for ggf in [
+            "(;GM[Othello]BO[8];)",
+            "(;GM[Othello]BO[8 *];)",
+            "(;GM[Othello]BO[0 *];)",
+            "(;GM[Othello]BO[0 - *];)",
+            "(;GM[Othello]BO[99999999999 - *];)",
+            "(;GM[Othello]BO[8 --- *];)",
+            "(;GM[Othello]BO[8",
+        ] {
+            assert!(parse_ggf(ggf).is_err(), "{}", ggf);
+        }
+
+        // An illegal move keeps the position from before the record
+        let output = engine_output(
+            "move f5\nset game (;GM[Othello]BO[8 ---------------------------O*------*O--------------------------- *]B[F5]W[A1];)\nhint 5\n",
+        );
+        assert!(output.contains("status Illegal move a1"));
+        // White answers f5 with d6, f4 or f6
+        assert_eq!(
+            3,
+            output
+                .lines()
+                .filter(|line| line.starts_with("search"))
+                .count()
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! Network play over TCP.
+//!
+//! One process hosts the game and the other joins it as a remote player. The host keeps the only real `Othello`,
+//! checks every move the remote player sends, and tells it about the board after every turn, so the two ends can't
+//! disagree about the position.
+//!
+//! The host sends one message per line:
+//!
+//! * `welcome <symbol> <player one> <player two>`: sent once, the symbol the remote player plays and the symbols of
+//!   both players in turn order
+//! * `board <board> <next>`: the `Board::encode`d board after a turn and the symbol of the player to move next
+//! * `position <board> <side> <opponent> [rules]`: a request for a move, answered with `move <row> <col>` (see `protocol`)
+//! * `illegal <reason>`: the last move was rejected, another `position` follows
+//! * `result <message>`: the game is over, the host closes the connection after this
+//!
+//! If the remote player disconnects, it forfeits. If the host disconnects, the game is abandoned.
+
+use crate::board::Board;
+use crate::player::minimax::MinimaxPlayer;
+use crate::player::spec::PlayerSpec;
+use crate::player::Player;
+use crate::protocol::{choose_move, format_reply, parse_reply, Request};
+use crate::rules::Rules;
+use crate::{ActivePlayer, Othello};
+
+use std::cell::{Cell, RefCell};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+/// The port used when none is given.
+pub const DEFAULT_PORT: u16 = 7450;
+
+/// Returned instead of a move once the remote player is gone. It is never on the board, so it's never legal.
+const NO_MOVE: (usize, usize) = (usize::MAX, usize::MAX);
+
+/// # The host's view of the player on the other end of the connection
+///
+/// Remote players forfeit when they disconnect. Illegal moves are rejected and asked for again.
+pub struct RemotePlayer {
+    symbol: char,
+    reader: RefCell<BufReader<TcpStream>>,
+    writer: RefCell<TcpStream>,
+    connected: Cell<bool>,
+}
+
+impl RemotePlayer {
+    /// Waits for a player to join on `listener` and welcomes it as `remote`, one of the players with `symbols`.
+    pub fn accept(
+        listener: &TcpListener,
+        symbols: (char, char),
+        remote: ActivePlayer,
+    ) -> io::Result<Self> {
+        let (stream, _) = listener.accept()?;
+        let symbol = match remote {
+            ActivePlayer::PlayerOne => symbols.0,
+            ActivePlayer::PlayerTwo => symbols.1,
+        };
+        let player = Self {
+            symbol,
+            reader: RefCell::new(BufReader::new(stream.try_clone()?)),
+            writer: RefCell::new(stream),
+            connected: Cell::new(true),
+        };
+        player.send(&format!("welcome {} {} {}", symbol, symbols.0, symbols.1));
+        if player.is_connected() {
+            Ok(player)
+        } else {
+            Err(io::Error::other("The player left before the game started."))
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.get()
+    }
+
+    /// Tells the remote player about the board after a turn.
+    pub fn sync(&self, game: &Othello) {
+        self.send(&format!(
+            "board {} {}",
+            game.board().encode(),
+            game.get_active_symbol()
+        ));
+    }
+
+    /// Tells the remote player how the game ended.
+    pub fn finish(&self, game: &Othello) {
+        let message = game
+            .get_winner()
+            .unwrap_or_else(|| "The game was abandoned.".to_string());
+        self.send(&format!("result {}", message));
+    }
+
+    /// Sends a line, noting the disconnect if it can't be sent.
+    fn send(&self, line: &str) {
+        if !self.is_connected() {
+            return;
+        }
+        let mut writer = self.writer.borrow_mut();
+        if writeln!(writer, "{}", line)
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            self.connected.set(false);
+        }
+    }
+
+    /// Reads a line, or `None` once the remote player is gone.
+    fn receive(&self) -> Option<String> {
+        let mut line = String::new();
+        match self.reader.borrow_mut().read_line(&mut line) {
+            Ok(0) | Err(_) => {
+                self.connected.set(false);
+                None
+            }
+            Ok(_) => Some(line.trim().to_string()),
+        }
+    }
+}
+
+impl Player for RemotePlayer {
+    fn get_symbol(&self) -> char {
+        self.symbol
+    }
+
+    fn get_move(&self, game: &Othello) -> (usize, usize) {
+        let request = Request::Position {
+            board: game.board().clone(),
+            side: self.symbol,
+            opponent: game.symbol_from_player(!game.active_player()),
+            rules: Rules::of_game(game),
+        };
+
+        while self.is_connected() {
+            self.send(&request.to_string());
+            let line = match self.receive() {
+                Some(line) => line,
+                None => break,
+            };
+            match parse_reply(&line) {
+                Some(Some((row, col))) if game.is_legal_move(row, col, self.symbol) => {
+                    return (row, col)
+                }
+                Some(Some((row, col))) => self.send(&format!(
+                    "illegal Row {}, Col {} isn't a legal move",
+                    row, col
+                )),
+                Some(None) => self.send("illegal You can't pass while you have moves"),
+                None => self.send(&format!("illegal `{}` isn't a move", line)),
+            }
+        }
+        NO_MOVE
+    }
+
+    fn forfeits_illegal_moves(&self) -> bool {
+        true
+    }
+}
+
+/// Plays `game` to the end, keeping `remote` (one of the game's players) in sync after every turn.
+///
+/// Returns the winner's player number like `Othello::get_winner_number`.
+pub fn host(game: &mut Othello, remote: &RemotePlayer, verbose: bool) -> usize {
+    remote.sync(game);
+    while game.has_more_moves() {
+        if verbose {
+            game.next_turn();
+        } else {
+            game.play_turn();
+        }
+        remote.sync(game);
+    }
+    remote.finish(game);
+    game.get_winner_number()
+}
+
+/// Connects to a host and plays as the player `spec` describes until the game ends.
+///
+/// Returns the host's description of the result. Fails if the host disconnects before the game is over.
+pub fn join(stream: TcpStream, spec: &PlayerSpec, verbose: bool) -> io::Result<String> {
+    let reader = BufReader::new(stream.try_clone()?);
+    play_remote(reader, stream, spec, verbose)
+}
+
+fn play_remote<R: BufRead, W: Write>(
+    reader: R,
+    mut writer: W,
+    spec: &PlayerSpec,
+    verbose: bool,
+) -> io::Result<String> {
+    let mut players = None;
+    for line in reader.lines() {
+        let line = line?;
+        let (command, rest) = match line.find(' ') {
+            Some(index) => (&line[..index], line[index + 1..].trim()),
+            None => (line.as_str(), ""),
+        };
+
+        match command {
+            "welcome" => {
+                let mut words = rest.split_whitespace().filter_map(|x| x.chars().next());
+                let symbol = words.next();
+                players = words.next().zip(words.next());
+                if let (true, Some(symbol)) = (verbose, symbol) {
+                    println!("Joined the game, you play {}.", symbol);
+                }
+            }
+            "board" if verbose => {
+                let mut words = rest.split_whitespace();
+                let board = words.next().and_then(Board::decode);
+                let next = words.next().and_then(|x| x.chars().next());
+                if let (Some(board), Some(next), Some(players)) = (board, next, players) {
+                    println!("{}", display(board, players, next));
+                }
+            }
+            "position" => {
+                let reply = match Request::parse(&line) {
+                    Some(Request::Position {
+                        board,
+                        side,
+                        opponent,
+                        rules,
+                    }) => choose_move(spec, board, side, opponent, &rules)
+                        .map_err(io::Error::other)?,
+                    _ => return Err(io::Error::other(format!("Bad request `{}`", line))),
+                };
+                writeln!(writer, "{}", format_reply(reply))?;
+                writer.flush()?;
+            }
+            "illegal" if verbose => println!("The host rejected the move: {}", rest),
+            "result" => return Ok(rest.to_string()),
+            _ => {}
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "The host disconnected before the game was over.",
+    ))
+}
+
+/// Renders a synced board the same way the host sees it.
+fn display(board: Board, (one, two): (char, char), next: char) -> String {
+    // Only used for their symbols, the host decides the moves
+    let (one, two) = (MinimaxPlayer::new(one), MinimaxPlayer::new(two));
+    let active = if next == one.get_symbol() {
+        ActivePlayer::PlayerOne
+    } else {
+        ActivePlayer::PlayerTwo
+    };
+    Othello::with_board(&one, &two, board, active).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::player::spec::PlayerKind;
+
+    use std::thread;
+
+    fn listen() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        (listener, address)
+    }
+
+    #[test]
+    fn plays_over_localhost() {
+        let (listener, address) = listen();
+        let client = thread::spawn(move || {
+            let spec = PlayerSpec {
+                depth: Some(2),
+                ..PlayerSpec::new(PlayerKind::Minimax)
+            };
+            join(TcpStream::connect(address).unwrap(), &spec, false).unwrap()
+        });
+
+        let local = MinimaxPlayer::new('X').with_depth(2);
+        let remote = RemotePlayer::accept(&listener, ('X', 'O'), ActivePlayer::PlayerTwo).unwrap();
+        let mut game = Othello::with_players(&local, &remote, 4, 4);
+        let winner = host(&mut game, &remote, false);
+
+        assert_eq!(None, game.forfeited());
+        assert!(!game.has_more_moves());
+        assert_eq!(game.get_winner(), Some(client.join().unwrap()));
+        assert!((0..=2).contains(&winner));
+    }
+
+    #[test]
+    fn rejects_illegal_moves_and_forfeits_on_disconnect() {
+        let (listener, address) = listen();
+        let client = thread::spawn(move || {
+            let stream = TcpStream::connect(address).unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut lines = vec![];
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                if line.starts_with("position") {
+                    writeln!(writer, "move 0 0").unwrap();
+                }
+                lines.push(std::mem::take(&mut line));
+                if lines.iter().any(|x| x.starts_with("illegal")) {
+                    // Leave without playing a legal move
+                    break;
+                }
+            }
+            lines
+        });
+
+        let local = MinimaxPlayer::new('O').with_depth(2);
+        let remote = RemotePlayer::accept(&listener, ('X', 'O'), ActivePlayer::PlayerOne).unwrap();
+        let mut game = Othello::with_players(&remote, &local, 4, 4);
+        let winner = host(&mut game, &remote, false);
+
+        let lines = client.join().unwrap();
+        assert!(lines[0].starts_with("welcome X X O"));
+        assert!(lines.iter().any(|x| x.starts_with("illegal")));
+        assert_eq!(Some(ActivePlayer::PlayerOne), game.forfeited());
+        assert_eq!(2, winner);
+    }
+
+    #[test]
+    fn host_disconnect_is_an_error() {
+        let (listener, address) = listen();
+        let host = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            writeln!(stream, "welcome O X O").unwrap();
+        });
+
+        let spec = PlayerSpec::new(PlayerKind::Minimax);
+        let result = join(TcpStream::connect(address).unwrap(), &spec, false);
+        host.join().unwrap();
+        assert_eq!(io::ErrorKind::UnexpectedEof, result.unwrap_err().kind());
+    }
+}
@@ -0,0 +1,313 @@
+//! Opening sets for matches between bots.
+//!
+//! Two deterministic bots that start from the same position play the same game every time. Starting each game from
+//! a different opening (a few moves in, XOT style) gives a match many different games, and checking the openings
+//! with a shallow search keeps either side from starting with a won game.
+//!
+//! Sets are saved one opening per line, with the moves written as `row,col`:
+//!
+//! ```text
+//! othlib-openings 1
+//! size 8 8
+//! 2,3 2,2 3,2 4,2
+//! 5,4 5,5 4,5 5,3
+//! ```
+
+use crate::player::minimax::MinimaxPlayer;
+use crate::rng::Rng;
+use crate::rules::Rules;
+use crate::Othello;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const HEADER: &str = "othlib-openings 1";
+
+/// The moves of an opening, played from the starting position. Passes are skipped over.
+pub type Opening = Vec<(usize, usize)>;
+
+/// Plays `opening` from the start of `game`, passing for a player without moves.
+pub fn play_opening(game: &mut Othello, opening: &[(usize, usize)]) -> Result<(), String> {
+    for &(row, col) in opening {
+        if game.has_more_moves() && !game.symbol_has_more_moves(game.get_active_symbol()) {
+            game.change_active_player();
+        }
+        game.try_move(row, col)
+            .map_err(|err| format!("The opening doesn't fit this board: {}", err))?;
+    }
+    Ok(())
+}
+
+/// Openings for one board size.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpeningSet {
+    pub rows: usize,
+    pub cols: usize,
+    pub openings: Vec<Opening>,
+}
+
+impl OpeningSet {
+    /// Loads a set written by `save`, for games played under `rules`.
+    pub fn load<P: AsRef<Path>>(path: P, rules: &Rules) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?, rules)
+    }
+
+    /// # Parses the opening file format
+    ///
+    /// Every opening is played from the start `rules` give the board, so an illegal move (or a size the rules
+    /// can't start) is reported here rather than in the middle of a match.
+    ///
+    /// ```
+    /// # use othlib::openings::OpeningSet;
+    /// # use othlib::rules::Rules;
+    /// let rules = Rules::default();
+    /// let set = OpeningSet::parse("othlib-openings 1\nsize 4 4\n0,2 0,1\n\n1,3\n", &rules).unwrap();
+    /// assert_eq!(vec![vec![(0, 2), (0, 1)], vec![(1, 3)]], set.openings);
+    /// assert!(OpeningSet::parse("othlib-openings 1\nsize 4 4\n0,0\n", &rules).is_err());
+    /// ```
+    pub fn parse(text: &str, rules: &Rules) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        if lines.next().map(str::trim) != Some(HEADER) {
+            return Err(invalid("Missing opening set header.".to_string()));
+        }
+        let size = lines
+            .next()
+            .and_then(
+                |line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+                    ["size", rows, cols] => {
+                        Some((rows.parse::<usize>().ok()?, cols.parse::<usize>().ok()?))
+                    }
+                    _ => None,
+                },
+            )
+            .ok_or_else(|| invalid("Missing size line.".to_string()))?;
+
+        let mut set = Self {
+            rows: size.0,
+            cols: size.1,
+            openings: vec![],
+        };
+        let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+        let start = rules
+            .start(&one, &two, set.rows, set.cols)
+            .map_err(invalid)?;
+        for line in lines {
+            let opening = line
+                .split_whitespace()
+                .map(|step| {
+                    let (row, col) = step.split_once(',')?;
+                    Some((row.parse().ok()?, col.parse().ok()?))
+                })
+                .collect::<Option<Opening>>()
+                .ok_or_else(|| invalid(format!("Malformed line: {}", line)))?;
+            play_opening(&mut start.clone(), &opening)
+                .map_err(|err| invalid(format!("{} ({})", err, line)))?;
+            set.openings.push(opening);
+        }
+        Ok(set)
+    }
+
+    /// Writes the set in the format `parse` reads.
+    pub fn format(&self) -> String {
+        let mut text = format!("{}\nsize {} {}\n", HEADER, self.rows, self.cols);
+        for opening in &self.openings {
+            let moves = opening
+                .iter()
+                .map(|(row, col)| format!("{},{}", row, col))
+                .collect::<Vec<_>>();
+            text += &moves.join(" ");
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Writes the set to a file `load` can read.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.format())
+    }
+}
+
+/// # Opening generator configuration
+#[derive(Clone, Debug)]
+pub struct Generator {
+    pub rows: usize,
+    pub cols: usize,
+    /// How many random moves each opening has.
+    pub moves: usize,
+    /// Only keep openings a search of `depth` moves scores within this far of even, if set.
+    pub balance: Option<isize>,
+    pub depth: usize,
+    pub seed: u64,
+    /// Openings are played (and balanced) under these rules.
+    pub rules: Rules,
+}
+
+impl Generator {
+    pub fn new(rows: usize, cols: usize, moves: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            moves,
+            balance: None,
+            depth: 4,
+            seed: 0,
+            rules: Rules::default(),
+        }
+    }
+
+    /// Picks up to `count` different openings.
+    ///
+    /// Fewer come back if there aren't that many different (or balanced) openings of that length. Openings that end
+    /// the game are skipped. Fails if the rules don't fit the board size.
+    pub fn generate(&self, count: usize) -> Result<OpeningSet, String> {
+        let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+        let start = self.rules.start(&one, &two, self.rows, self.cols)?;
+        let mut rng = Rng::with_seed(self.seed);
+        let mut openings: Vec<Opening> = vec![];
+
+        // Give up after a while on small boards that don't have `count` different openings
+        for _ in 0..count.saturating_mul(50) {
+            if openings.len() == count {
+                break;
+            }
+            let mut game = start.clone();
+            let mut opening = vec![];
+            while opening.len() < self.moves && game.has_more_moves() {
+                let symbol = game.get_active_symbol();
+                let successors = game.successors(symbol);
+                if successors.is_empty() {
+                    game.change_active_player();
+                    continue;
+                }
+                let (row, col) = successors[rng.below(successors.len())];
+                game.play_move(row, col, symbol);
+                opening.push((row, col));
+            }
+            if game.has_more_moves() && !openings.contains(&opening) && self.is_balanced(&game) {
+                openings.push(opening);
+            }
+        }
+
+        Ok(OpeningSet {
+            rows: self.rows,
+            cols: self.cols,
+            openings,
+        })
+    }
+
+    fn is_balanced(&self, game: &Othello) -> bool {
+        let limit = match self.balance {
+            Some(limit) => limit,
+            None => return true,
+        };
+        let mut game = game.clone();
+        if !game.symbol_has_more_moves(game.get_active_symbol()) {
+            game.change_active_player();
+        }
+        let player = MinimaxPlayer::new(game.get_active_symbol()).with_depth(self.depth);
+        player.search(&game).score.abs() <= limit
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn generates_different_balanced_openings() {
+        let mut generator = Generator::new(6, 6, 4);
+        generator.seed = 11;
+        let set = generator.generate(10).unwrap();
+        assert_eq!(10, set.openings.len());
+        assert!(set.openings.iter().all(|opening| opening.len() == 4));
+        for (index, opening) in set.openings.iter().enumerate() {
+            assert!(!set.openings[index + 1..].contains(opening));
+        }
+        assert_eq!(
+            set,
+            OpeningSet::parse(&set.format(), &Rules::default()).unwrap()
+        );
+
+        generator.balance = Some(0);
+        generator.depth = 2;
+        let balanced = generator.generate(5).unwrap();
+        assert!(!balanced.openings.is_empty());
+        for opening in &balanced.openings {
+            let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+            let mut game = Othello::with_players(&one, &two, 6, 6);
+            play_opening(&mut game, opening).unwrap();
+            assert!(generator.is_balanced(&game));
+        }
+
+        // A 4x4 board only has 4 different first moves
+        assert_eq!(
+            4,
+            Generator::new(4, 4, 1).generate(10).unwrap().openings.len()
+        );
+    }
+
+    #[test]
+    fn books_load_under_the_rules_they_were_made_for() {
+        use crate::rules::Topology;
+
+        let rules = Rules {
+            topology: Topology::Torus,
+            ..Rules::default()
+        };
+        let mut generator = Generator::new(4, 4, 4);
+        generator.rules = rules.clone();
+        let set = generator.generate(10).unwrap();
+        assert_eq!(set, OpeningSet::parse(&set.format(), &rules).unwrap());
+        // Lines wrap around the edges, so some of these moves aren't legal on a flat board
+        assert!(OpeningSet::parse(&set.format(), &Rules::default()).is_err());
+    }
+
+    #[test]
+    fn free_setup_books_start_from_an_empty_middle() {
+        use crate::rules::Setup;
+
+        let rules = Rules {
+            setup: Setup::Free,
+            ..Rules::default()
+        };
+        let mut generator = Generator::new(6, 6, 3);
+        generator.rules = rules.clone();
+        let set = generator.generate(5).unwrap();
+        assert_eq!(set, OpeningSet::parse(&set.format(), &rules).unwrap());
+        // The first moves fill the middle, which the fixed setup starts with
+        assert!(OpeningSet::parse(&set.format(), &Rules::default()).is_err());
+    }
+
+    #[test]
+    fn books_keep_to_the_starting_layout() {
+        use crate::rules::Layout;
+
+        let parallel = Rules {
+            layout: Layout::Parallel,
+            ..Rules::default()
+        };
+        let mut generator = Generator::new(6, 6, 3);
+        generator.rules = parallel.clone();
+        let set = generator.generate(5).unwrap();
+        assert_eq!(set, OpeningSet::parse(&set.format(), &parallel).unwrap());
+        assert!(OpeningSet::parse(&set.format(), &Rules::default()).is_err());
+
+        // Odd sizes start with a cross around the center
+        let set = Generator::new(5, 5, 3).generate(5).unwrap();
+        assert_eq!(5, set.openings.len());
+        assert_eq!(
+            set,
+            OpeningSet::parse(&set.format(), &Rules::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn bad_sizes_are_errors() {
+        for size in ["size 1 8", "size 0 0", "size 8"] {
+            let text = format!("{}\n{}\n", HEADER, size);
+            assert!(OpeningSet::parse(&text, &Rules::default()).is_err());
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! A container module for players that run as another process
+//!
+//! The process is sent each position on stdin and answers with its move on stdout, using the line protocol from
+//! `othlib::protocol`. A player that takes too long, exits, or answers with something other than a legal move
+//! forfeits the game.
+
+use crate::player::Player;
+use crate::protocol::{parse_reply, Request};
+use crate::rules::Rules;
+use crate::Othello;
+
+use std::cell::{Cell, RefCell};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// How long an engine gets per move unless told otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returned instead of a move once the engine has failed. It is never on the board, so it's never legal.
+const NO_MOVE: (usize, usize) = (usize::MAX, usize::MAX);
+
+/// # A player backed by an engine process
+///
+/// The process is started when the player is created and killed when the player is dropped.
+pub struct ExternalPlayer {
+    symbol: char,
+    timeout: Duration,
+    child: RefCell<Child>,
+    stdin: RefCell<ChildStdin>,
+    replies: Receiver<String>,
+    failure: RefCell<Option<String>>,
+    failed: Cell<bool>,
+}
+
+impl ExternalPlayer {
+    /// Starts `program` with `args` as the engine playing `symbol`.
+    pub fn launch(
+        symbol: char,
+        program: &str,
+        args: &[String],
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        // Read replies on a separate thread so a silent engine can be timed out
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            symbol,
+            timeout,
+            child: RefCell::new(child),
+            stdin: RefCell::new(stdin),
+            replies,
+            failure: RefCell::new(None),
+            failed: Cell::new(false),
+        })
+    }
+
+    /// Why the engine stopped playing, if it has.
+    pub fn failure(&self) -> Option<String> {
+        self.failure.borrow().clone()
+    }
+
+    fn fail(&self, reason: String) -> (usize, usize) {
+        eprintln!("Engine for {} failed: {}", self.symbol, reason);
+        self.failed.set(true);
+        *self.failure.borrow_mut() = Some(reason);
+        NO_MOVE
+    }
+
+    /// Describes how the engine process exited, waiting briefly for it to finish exiting.
+    fn exit_reason(&self) -> String {
+        let mut child = self.child.borrow_mut();
+        for _ in 0..10 {
+            if let Ok(Some(status)) = child.try_wait() {
+                return format!("the engine exited ({})", status);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        "the engine closed its output".to_string()
+    }
+}
+
+impl Player for ExternalPlayer {
+    fn get_symbol(&self) -> char {
+        self.symbol
+    }
+
+    fn get_move(&self, game: &Othello) -> (usize, usize) {
+        if self.failed.get() {
+            return NO_MOVE;
+        }
+
+        let request = Request::Position {
+            board: game.board().clone(),
+            side: self.symbol,
+            opponent: game.symbol_from_player(!game.active_player()),
+            rules: Rules::of_game(game),
+        };
+        let mut stdin = self.stdin.borrow_mut();
+        let sent = writeln!(stdin, "{}", request).and_then(|_| stdin.flush());
+        drop(stdin);
+        if sent.is_err() {
+            return self.fail(self.exit_reason());
+        }
+
+        match self.replies.recv_timeout(self.timeout) {
+            Ok(line) => match parse_reply(&line) {
+                Some(Some(coords)) => coords,
+                Some(None) => self.fail("the engine passed with moves left".to_string()),
+                None => self.fail(format!("the engine answered `{}`", line)),
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                self.fail(format!("no move within {:?}", self.timeout))
+            }
+            Err(RecvTimeoutError::Disconnected) => self.fail(self.exit_reason()),
+        }
+    }
+
+    fn forfeits_illegal_moves(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for ExternalPlayer {
+    fn drop(&mut self) {
+        let _ = writeln!(self.stdin.get_mut(), "{}", Request::Quit);
+        let child = self.child.get_mut();
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+    use crate::player::minimax::MinimaxPlayer;
+    use crate::ActivePlayer;
+
+    fn shell(symbol: char, script: &str, timeout: Duration) -> ExternalPlayer {
+        let args = vec!["-c".to_string(), script.to_string()];
+        ExternalPlayer::launch(symbol, "sh", &args, timeout).unwrap()
+    }
+
+    /// Plays a game between `engine` and a minimax player, returning the winner and who forfeited.
+    fn play_against(engine: &ExternalPlayer) -> (usize, Option<ActivePlayer>, Option<String>) {
+        let opponent = MinimaxPlayer::new('O').with_depth(1);
+        let mut game = Othello::with_players(engine, &opponent, 4, 4);
+        game.run_silent();
+        (
+            game.get_winner_number(),
+            game.forfeited(),
+            game.get_winner(),
+        )
+    }
+
+    #[test]
+    fn illegal_move_forfeits() {
+        let engine = shell(
+            'X',
+            "while read line; do echo move 0 0; done",
+            DEFAULT_TIMEOUT,
+        );
+        let (winner, forfeited, message) = play_against(&engine);
+        assert_eq!(2, winner);
+        assert_eq!(Some(ActivePlayer::PlayerOne), forfeited);
+        assert!(message.unwrap().contains("forfeit"));
+    }
+
+    #[test]
+    fn crash_forfeits() {
+        let engine = shell('X', "read line; exit 3", DEFAULT_TIMEOUT);
+        assert_eq!(2, play_against(&engine).0);
+        assert!(engine.failure().unwrap().contains("exit"));
+    }
+
+    #[test]
+    fn timeout_forfeits() {
+        let engine = shell('X', "read line; sleep 5", Duration::from_millis(100));
+        assert_eq!(2, play_against(&engine).0);
+        assert!(engine.failure().unwrap().contains("no move"));
+    }
+}
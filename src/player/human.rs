@@ -1,6 +1,8 @@
 //! A container module for the human player
 
+use crate::board::Board;
 use crate::player::Player;
+use crate::rolit::{Rolit, RolitPlayer};
 use crate::Othello;
 use std::io::prelude::*;
 
@@ -13,7 +15,7 @@ impl HumanPlayer {
         &self,
         prompt: &'static str,
         upper_bound: usize,
-        game: &Othello,
+        successors: &[(usize, usize)],
         row_choice: &mut Option<usize>,
     ) -> Option<usize> {
         let mut val = "".to_string();
@@ -26,7 +28,7 @@ impl HumanPlayer {
         match (val, &row_choice) {
             ("?", Some(x)) => {
                 let mut printed = false;
-                for (row, col) in game.successors(self.get_symbol()) {
+                for &(row, col) in successors {
                     if row != *x {
                         continue;
                     }
@@ -40,7 +42,7 @@ impl HumanPlayer {
                 return None;
             }
             ("?", None) => {
-                for (row, col) in game.successors(self.get_symbol()) {
+                for &(row, col) in successors {
                     println!("Row: {}, Col: {}", row, col);
                 }
                 return None;
@@ -71,25 +73,61 @@ impl Player for HumanPlayer {
 
     /// Prompts a human via stdin for moves.
     fn get_move(&self, game: &Othello) -> (usize, usize) {
-        let board = game.board();
+        self.prompt(game.board(), &game.successors(self.0))
+    }
+
+    /// Asks whether to swap, until the answer is yes or no.
+    fn wants_swap(&self, _game: &Othello) -> bool {
+        loop {
+            let mut answer = String::new();
+            print!("Swap and take over the first move? (y/n): ");
+            std::io::stdout().flush().unwrap();
+            std::io::stdin().read_line(&mut answer).unwrap();
+            match answer.trim() {
+                "y" | "yes" => return true,
+                "n" | "no" => return false,
+                _ => eprintln!("Enter y or n."),
+            }
+        }
+    }
+}
+
+impl RolitPlayer for HumanPlayer {
+    fn get_symbol(&self) -> char {
+        self.0
+    }
+
+    /// Prompts a human via stdin for moves.
+    fn get_move(&self, game: &Rolit) -> (usize, usize) {
+        self.prompt(game.board(), &game.successors(self.0))
+    }
+}
+
+impl HumanPlayer {
+    /// Asks for a row and then a column until they name an empty cell. `?` lists `successors`.
+    fn prompt(&self, board: &Board, successors: &[(usize, usize)]) -> (usize, usize) {
         let mut row = None;
         let mut col = None;
 
         loop {
             if row.is_none() {
-                row = self.move_helper("Enter row (or ?): ", board.rows(), game, &mut row);
+                row = self.move_helper("Enter row (or ?): ", board.rows(), successors, &mut row);
                 if row.is_none() {
                     continue;
                 }
             }
             if col.is_none() {
-                col = self.move_helper("Enter col (or ?): ", board.cols(), game, &mut row);
+                col = self.move_helper("Enter col (or ?): ", board.cols(), successors, &mut row);
                 if col.is_none() {
                     continue;
                 }
             }
             // These expects are safe due to the conditions above. Changing them can result in unexpected behavior
-            if board.is_cell_empty(row.expect(LOOP_ERR), col.expect(LOOP_ERR)) {
+            if board.is_blocked(row.expect(LOOP_ERR), col.expect(LOOP_ERR)) {
+                eprintln!("That cell isn't part of the board");
+                row = None;
+                col = None;
+            } else if board.is_cell_empty(row.expect(LOOP_ERR), col.expect(LOOP_ERR)) {
                 return (row.expect(LOOP_ERR), col.expect(LOOP_ERR));
             } else {
                 eprintln!("That cell is occupied");
@@ -1,60 +1,303 @@
 //! A container module for the minimax player
 
+use crate::eval::{outcome, Evaluate};
 use crate::player::Player;
+use crate::rng::Rng;
 use crate::Othello;
 
-/// A player that makes moves based on a simple minimax algorithm (w/o alpha-beta pruning) by recursivly playing the game.
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+/// How often (in nodes) the search checks whether it's out of time.
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+/// A move in a principal variation. `None` is a pass.
+pub type PvMove = Option<(usize, usize)>;
+
+/// Limits on how long a search may run. A search without limits plays the game out to the end.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchLimits {
+    /// The deepest (in moves) the search may look.
+    pub depth: Option<usize>,
+    /// The longest the search may take. The deepest iteration finished in time is used.
+    pub time: Option<Duration>,
+}
+
+/// # What a search found and how much work it took.
+#[derive(Clone, Debug, Default)]
+pub struct SearchInfo {
+    /// The chosen move, or `None` if there was no legal move.
+    pub best_move: Option<(usize, usize)>,
+    /// The expected result for the searching player under the game's rules: the final disc differential, negated in
+    /// misère games. Positive is winning.
+    pub score: isize,
+    /// The depth of the last finished iteration.
+    pub depth: usize,
+    /// Whether every line was searched to the end of the game, making `score` exact.
+    pub exact: bool,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    /// The line of play the search expects, starting with `best_move`.
+    pub pv: Vec<PvMove>,
+    pub tt_probes: u64,
+    pub tt_hits: u64,
+}
+
+impl SearchInfo {
+    pub fn nodes_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.nodes as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    /// The fraction of transposition table lookups that found an entry.
+    pub fn tt_hit_rate(&self) -> f64 {
+        if self.tt_probes > 0 {
+            self.tt_hits as f64 / self.tt_probes as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Formats a principal variation as `row,col` pairs separated by spaces.
 ///
-/// On a 4x4 space, this player cannot lose if playing as player 2.
+/// # Examples
 ///
-/// _Note_: This algorithm has a very large time complexity. 6x6 can generate one move on a scale of days or weeks depending on your computer.
-/// However, a 4x4 grid can be done in a matter of seconds or subseconds.
-pub struct MinimaxPlayer(pub char);
+/// ```
+/// # use othlib::player::minimax::format_pv;
+/// assert_eq!("3,1 pass 0,2", format_pv(&[Some((3, 1)), None, Some((0, 2))]));
+/// ```
+pub fn format_pv(pv: &[PvMove]) -> String {
+    pv.iter()
+        .map(|step| match step {
+            Some((row, col)) => format!("{},{}", row, col),
+            None => "pass".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-impl MinimaxPlayer {
+/// Parses moves written by `format_pv`, returning `None` if any of them is malformed.
+///
+/// # Examples
+///
+/// ```
+/// # use othlib::player::minimax::parse_pv;
+/// assert_eq!(Some(vec![Some((3, 1)), None, Some((0, 2))]), parse_pv("3,1 pass 0,2"));
+/// assert_eq!(None, parse_pv("3,1 0"));
+/// ```
+pub fn parse_pv(text: &str) -> Option<Vec<PvMove>> {
+    text.split_whitespace()
+        .map(|step| match step {
+            "pass" => Some(None),
+            _ => {
+                let (row, col) = step.split_once(',')?;
+                Some(Some((row.parse().ok()?, col.parse().ok()?)))
+            }
+        })
+        .collect()
+}
+
+impl fmt::Display for SearchInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "depth {}{} score {} nodes {} nps {:.0} time {:.3}s tt {:.1}% pv {}",
+            self.depth,
+            if self.exact { " (end)" } else { "" },
+            self.score,
+            self.nodes,
+            self.nodes_per_second(),
+            self.elapsed.as_secs_f64(),
+            self.tt_hit_rate() * 100.0,
+            format_pv(&self.pv)
+        )
+    }
+}
+
+/// # How strong a minimax player plays.
+///
+/// Weaker levels look fewer moves ahead, blur their evaluations with random noise and sometimes play a random move
+/// on purpose.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Difficulty {
+    pub depth: usize,
+    /// Evaluations at the depth limit are moved by up to this many discs in either direction.
+    pub noise: isize,
+    /// The chance of playing a random move other than the best one.
+    pub blunder_chance: f64,
+}
+
+impl Difficulty {
+    pub const MIN_LEVEL: u8 = 1;
+    pub const MAX_LEVEL: u8 = 10;
+
+    /// Returns the settings for a level from 1 (weakest) to 10 (strongest).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use othlib::player::minimax::Difficulty;
+    /// let easy = Difficulty::level(1).unwrap();
+    /// let hard = Difficulty::level(10).unwrap();
+    /// assert!(easy.depth < hard.depth);
+    /// assert_eq!(0.0, hard.blunder_chance);
+    /// assert!(Difficulty::level(11).is_none());
+    /// ```
+    pub fn level(level: u8) -> Option<Self> {
+        let (depth, noise, blunder_chance) = match level {
+            1 => (1, 8, 0.5),
+            2 => (1, 6, 0.35),
+            3 => (2, 5, 0.25),
+            4 => (2, 4, 0.15),
+            5 => (3, 3, 0.1),
+            6 => (3, 2, 0.05),
+            7 => (4, 1, 0.02),
+            8 => (5, 0, 0.0),
+            9 => (6, 0, 0.0),
+            10 => (8, 0, 0.0),
+            _ => return None,
+        };
+        Some(Self {
+            depth,
+            noise,
+            blunder_chance,
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Entry {
+    remaining: usize,
+    score: isize,
+    bound: Bound,
+    best: PvMove,
+}
+
+/// The state of one search: counters, the transposition table and the clock.
+struct Search<'p> {
+    player: &'p MinimaxPlayer,
+    table: HashMap<u64, Entry>,
+    nodes: u64,
+    tt_probes: u64,
+    tt_hits: u64,
+    deadline: Option<Instant>,
+    aborted: bool,
+    /// Set whenever a position had to be guessed at instead of played out.
+    guessed: bool,
+}
+
+impl<'p> Search<'p> {
     /// # An implementation of the minimax recursive algorithm for finding scores.
     ///
     /// Read more about it on wikipedia: https://en.wikipedia.org/wiki/Computer_Othello#Search_techniques
+    ///
+    /// Scores are always from the searching player's point of view. Returns the score and the expected line of play.
     fn minimax(
-        &self,
+        &mut self,
         game: &mut Othello,
+        remaining: usize,
         mut alpha: isize,
         mut beta: isize,
         maximize: bool,
-    ) -> ((usize, usize), isize) {
-        let player_symbol = self.get_symbol();
+    ) -> (isize, Vec<PvMove>) {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(TIME_CHECK_INTERVAL)
+            && self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return (0, vec![]);
+        }
+
+        let player_symbol = self.player.get_symbol();
         let opponent_symbol = game.symbol_from_player(
             //The ! changes an ActivePlayer::PlayerOne into ActivePlayer::PlayerTwo and vice-versa
             !game
-                .player_from_symbol(self.get_symbol())
+                .player_from_symbol(player_symbol)
                 .expect("Tried to match symbol for someone not in the game."),
         );
         //If we're maximizing, we increase from isize::MIN, otherwise we decrease from isize::MAX
         let (mut best_res, turn_symbol) = if maximize {
-            (std::isize::MIN, player_symbol)
+            (isize::MIN, player_symbol)
         } else {
-            (std::isize::MAX, opponent_symbol)
+            (isize::MAX, opponent_symbol)
         };
 
         //If there is no more game to play
         if !game.has_more_moves() {
-            return ((0, 0), self.utility(&game));
+            return (self.player.utility(game), vec![]);
+        }
+
+        //If we've looked as far ahead as we're allowed to, guess
+        if remaining == 0 {
+            self.guessed = true;
+            return (self.player.evaluate(game), vec![]);
         }
 
         //If we're at this point the game isn't over but we can't move so let's let our opponent move.
         if !game.symbol_has_more_moves(turn_symbol) {
-            return self.minimax(game, alpha, beta, !maximize);
+            let (score, mut pv) = self.minimax(game, remaining, alpha, beta, !maximize);
+            pv.insert(0, None);
+            return (score, pv);
         }
 
-        let mut best_coords = (0, 0);
+        let key = position_key(game, maximize);
+        self.tt_probes += 1;
+        let mut successors = game.successors(turn_symbol);
+        if let Some(entry) = self.table.get(&key).copied() {
+            self.tt_hits += 1;
+            if entry.remaining >= remaining {
+                let usable = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.score >= beta,
+                    Bound::Upper => entry.score <= alpha,
+                };
+                if usable {
+                    return (entry.score, entry.best.into_iter().map(Some).collect());
+                }
+            }
+            // Try the move that was best last time first, it's the most likely to cause a cutoff
+            if let Some(best) = entry
+                .best
+                .and_then(|best| successors.iter().position(|&x| x == best))
+            {
+                successors[..=best].rotate_right(1);
+            }
+        }
 
-        for (row, col) in game.successors(turn_symbol) {
+        let (original_alpha, original_beta) = (alpha, beta);
+        let mut best_pv = vec![];
+
+        for (row, col) in successors {
             let mut new_game: Othello = game.clone();
             new_game.play_move(row, col, turn_symbol);
-            let result = self.minimax(&mut new_game, alpha, beta, !maximize).1;
+            let (result, pv) = self.minimax(&mut new_game, remaining - 1, alpha, beta, !maximize);
+            if self.aborted {
+                return (0, vec![]);
+            }
             if (maximize && result > best_res) || (!maximize && result < best_res) {
                 best_res = result;
-                best_coords = (row, col);
+                best_pv = pv;
+                best_pv.insert(0, Some((row, col)));
             }
             if maximize {
                 alpha = alpha.max(result);
@@ -66,30 +309,391 @@ impl MinimaxPlayer {
             }
         }
 
-        (best_coords, best_res)
+        let bound = if best_res <= original_alpha {
+            Bound::Upper
+        } else if best_res >= original_beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(
+            key,
+            Entry {
+                remaining,
+                score: best_res,
+                bound,
+                best: best_pv.first().copied().flatten(),
+            },
+        );
+
+        (best_res, best_pv)
+    }
+}
+
+/// Hashes the board together with whose turn it is in the search.
+fn position_key(game: &Othello, maximize: bool) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    game.board().hash(&mut hasher);
+    maximize.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A player that makes moves based on a minimax algorithm with alpha-beta pruning by recursivly playing the game.
+///
+/// On a 4x4 space, this player cannot lose if playing as player 2.
+///
+/// By default the game is searched to the end. Search limits can be set with `with_depth` and `with_limits`, in which
+/// case the search deepens one move at a time and positions at the limit are scored by the evaluator
+/// (see `with_evaluator`) or by counting discs.
+///
+/// _Note_: A full search has a very large time complexity. 6x6 can generate one move on a scale of days or weeks depending on your computer.
+/// However, a 4x4 grid can be done in a matter of seconds or subseconds.
+pub struct MinimaxPlayer {
+    symbol: char,
+    limits: SearchLimits,
+    evaluator: Option<Box<dyn Evaluate>>,
+    thinking: bool,
+    noise: isize,
+    blunder_chance: f64,
+    rng: RefCell<Rng>,
+}
+
+impl MinimaxPlayer {
+    /// Creates a player that searches to the end of the game.
+    pub fn new(symbol: char) -> Self {
+        Self {
+            symbol,
+            limits: SearchLimits::default(),
+            evaluator: None,
+            thinking: false,
+            noise: 0,
+            blunder_chance: 0.0,
+            rng: RefCell::new(Rng::from_time()),
+        }
+    }
+
+    /// Plays at a difficulty level. See `Difficulty::level`; levels outside of 1 to 10 are clamped.
+    pub fn with_level(self, level: u8) -> Self {
+        let level = level.clamp(Difficulty::MIN_LEVEL, Difficulty::MAX_LEVEL);
+        self.with_difficulty(Difficulty::level(level).expect("Clamped levels always exist."))
+    }
+
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.limits.depth = Some(difficulty.depth);
+        self.noise = difficulty.noise;
+        self.blunder_chance = difficulty.blunder_chance;
+        self
+    }
+
+    /// Seeds the randomness used by difficulty levels, making the player's choices repeatable.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.rng.replace(Rng::with_seed(seed));
+        self
+    }
+
+    /// Limits the search to `depth` moves ahead.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.limits.depth = Some(depth);
+        self
+    }
+
+    /// Replaces all of the search limits.
+    pub fn with_limits(mut self, limits: SearchLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Scores positions at the depth limit with `evaluator` instead of the disc count.
+    pub fn with_evaluator<E: Evaluate + 'static>(mut self, evaluator: E) -> Self {
+        self.evaluator = Some(Box::new(evaluator));
+        self
+    }
+
+    /// Prints a line of search information after every finished iteration.
+    pub fn with_thinking(mut self, thinking: bool) -> Self {
+        self.thinking = thinking;
+        self
+    }
+
+    pub fn limits(&self) -> SearchLimits {
+        self.limits
+    }
+
+    /// Searches `game` for this player and returns the best move, its score and statistics about the search.
+    ///
+    /// Without limits the score is exact: the result (see `eval::outcome`) with best play from both sides.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::Othello;
+    /// let player_one = MinimaxPlayer::new('X');
+    /// let player_two = MinimaxPlayer::new('O');
+    /// let game = Othello::with_players(&player_one, &player_two, 4, 4);
+    ///
+    /// let info = player_one.search(&game);
+    /// assert!(info.exact);
+    /// assert_eq!(info.best_move, info.pv[0]);
+    /// assert!(info.score < 0);
+    /// ```
+    pub fn search(&self, game: &Othello) -> SearchInfo {
+        let start = Instant::now();
+        let board = game.board();
+        let empties = board.empties();
+        let max_depth = self.limits.depth.unwrap_or(empties).min(empties);
+        let mut search = Search {
+            player: self,
+            table: HashMap::new(),
+            nodes: 0,
+            tt_probes: 0,
+            tt_hits: 0,
+            deadline: self.limits.time.map(|time| start + time),
+            aborted: false,
+            guessed: false,
+        };
+
+        // Without any limits there's nothing to gain from deepening gradually
+        let first_depth = if self.limits == SearchLimits::default() {
+            max_depth
+        } else {
+            max_depth.min(1)
+        };
+
+        let mut info = SearchInfo::default();
+        for depth in first_depth..=max_depth {
+            search.guessed = false;
+            let (score, pv) =
+                search.minimax(&mut game.clone(), depth, isize::MIN, isize::MAX, true);
+            info.nodes = search.nodes;
+            info.tt_probes = search.tt_probes;
+            info.tt_hits = search.tt_hits;
+            info.elapsed = start.elapsed();
+            // An unfinished iteration is thrown away in favour of the last finished one
+            if search.aborted {
+                break;
+            }
+
+            info.score = score;
+            info.pv = pv;
+            info.depth = depth;
+            info.exact = !search.guessed;
+            info.best_move = info.pv.first().copied().flatten();
+            if self.thinking {
+                println!("{}", info);
+            }
+            // Nothing was guessed, so searching deeper can't change anything
+            if info.exact {
+                break;
+            }
+        }
+
+        // If not even the first iteration finished, any legal move is better than none
+        if info.best_move.is_none() {
+            info.best_move = game.successors(self.get_symbol()).first().copied();
+        }
+
+        info
     }
 
     fn utility(&self, game: &Othello) -> isize {
-        let opponent_symbol = game.symbol_from_player(
-            !game
-                .player_from_symbol(self.get_symbol())
-                .expect("Tried to match symbol for someone not in the game."),
-        );
-        let counts = game.board().char_counts();
-        let our_count = *counts.get(&self.get_symbol()).unwrap_or(&0) as isize;
-        let opponent_count = *counts.get(&opponent_symbol).unwrap_or(&0) as isize;
+        outcome(game, self.get_symbol())
+    }
 
-        our_count - opponent_count
+    fn evaluate(&self, game: &Othello) -> isize {
+        // Evaluations estimate the disc differential, which the variant decides the worth of
+        let score = match &self.evaluator {
+            Some(evaluator) => game
+                .variant()
+                .outcome(evaluator.evaluate(game, self.get_symbol())),
+            None => self.utility(game),
+        };
+        if self.noise > 0 {
+            let spread = self.rng.borrow_mut().below(2 * self.noise as usize + 1) as isize;
+            score + spread - self.noise
+        } else {
+            score
+        }
     }
 }
 
 impl Player for MinimaxPlayer {
     fn get_symbol(&self) -> char {
-        self.0
+        self.symbol
     }
 
+    /// Searches for the best move, then (depending on the difficulty) sometimes plays another one instead.
     fn get_move(&self, game: &Othello) -> (usize, usize) {
-        self.minimax(&mut game.clone(), std::isize::MIN, std::isize::MAX, true)
-            .0
+        let best_move = self.search(game).best_move.unwrap_or((0, 0));
+        let mut rng = self.rng.borrow_mut();
+        if self.blunder_chance > 0.0 && rng.next_f64() < self.blunder_chance {
+            let others = game
+                .successors(self.get_symbol())
+                .into_iter()
+                .filter(|&coords| coords != best_move)
+                .collect::<Vec<_>>();
+            if !others.is_empty() {
+                return others[rng.below(others.len())];
+            }
+        }
+        best_move
+    }
+
+    /// Swaps if the position after swapping is worth more than the best move here. After a swap the opponent is to
+    /// move, so that's scored by searching each of their replies.
+    fn wants_swap(&self, game: &Othello) -> bool {
+        let stay = self.search(game).score;
+        let mut swapped = game.clone();
+        if swapped.swap().is_err() {
+            return false;
+        }
+        let opponent = swapped.get_active_symbol();
+        let swap = swapped
+            .successors(opponent)
+            .into_iter()
+            .map(|(row, col)| {
+                let mut reply = swapped.clone();
+                reply.play_move(row, col, opponent);
+                self.search(&reply).score
+            })
+            .min()
+            // Searches play for this player whoever's turn it is, so a pass needs no special case
+            .unwrap_or_else(|| self.search(&swapped).score);
+        swap > stay
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deepening_matches_full_search() {
+        let player_one = MinimaxPlayer::new('X');
+        let player_two = MinimaxPlayer::new('O');
+        let game = Othello::with_players(&player_one, &player_two, 4, 4);
+
+        let full = player_one.search(&game);
+        let deepened = MinimaxPlayer::new('X').with_depth(20).search(&game);
+        assert!(full.exact && deepened.exact);
+        assert_eq!(full.score, deepened.score);
+        assert!(deepened.tt_hits > 0);
+    }
+
+    #[test]
+    fn misere_search_plays_for_fewer_discs() {
+        use crate::rules::Variant;
+
+        let player_one = MinimaxPlayer::new('X');
+        let player_two = MinimaxPlayer::new('O');
+        let standard = Othello::with_players(&player_one, &player_two, 4, 4);
+        let misere = standard.clone().with_variant(Variant::Misere);
+
+        // Perfect play reaches the score the search promised, judged by the game's own rules
+        for mut game in [standard, misere] {
+            let score = player_one.search(&game).score;
+            game.run_silent();
+            assert_eq!(score, outcome(&game, 'X'));
+        }
+    }
+
+    #[test]
+    fn swaps_only_into_a_better_position() {
+        use crate::rules::Variant;
+
+        let player_one = MinimaxPlayer::new('X');
+        let player_two = MinimaxPlayer::new('O');
+        for variant in [Variant::Standard, Variant::Misere] {
+            let mut game = Othello::with_players(&player_one, &player_two, 4, 4)
+                .with_variant(variant)
+                .with_swap_rule(true);
+            game.try_move(0, 2).unwrap();
+            // O wins 4x4 by moving second, but loses the misère game, so it only swaps in that one
+            let stay = player_two.search(&game).score;
+            assert_eq!(variant == Variant::Misere, stay < 0);
+            assert_eq!(stay < 0, player_two.wants_swap(&game));
+        }
+    }
+
+    /// Plays `games` games with each color and returns `stronger`'s points (1 per win, half per tie)
+    fn points(stronger: u8, weaker: u8, games: u64) -> f64 {
+        let mut points = 0.0;
+        for seed in 0..games {
+            for &swap in &[false, true] {
+                let strong = MinimaxPlayer::new(if swap { 'O' } else { 'X' })
+                    .with_level(stronger)
+                    .with_seed(seed);
+                let weak = MinimaxPlayer::new(if swap { 'X' } else { 'O' })
+                    .with_level(weaker)
+                    .with_seed(seed + 100);
+                let mut game = if swap {
+                    Othello::with_players(&weak, &strong, 6, 6)
+                } else {
+                    Othello::with_players(&strong, &weak, 6, 6)
+                };
+                points += match (game.run_silent(), swap) {
+                    (0, _) => 0.5,
+                    (1, false) | (2, true) => 1.0,
+                    _ => 0.0,
+                };
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn levels_are_monotonic() {
+        // No setting gets weaker from one level to the next. No time limit is the strongest one
+        let unlimited = |time: Option<Duration>| time.unwrap_or(Duration::MAX);
+        for level in Difficulty::MIN_LEVEL..Difficulty::MAX_LEVEL {
+            let (weaker, stronger) = (
+                Difficulty::level(level).unwrap(),
+                Difficulty::level(level + 1).unwrap(),
+            );
+            let limits = (
+                MinimaxPlayer::new('X').with_level(level).limits(),
+                MinimaxPlayer::new('X').with_level(level + 1).limits(),
+            );
+            assert!(weaker.depth <= stronger.depth, "Level {}", level + 1);
+            assert!(
+                unlimited(limits.0.time) <= unlimited(limits.1.time),
+                "Level {}",
+                level + 1
+            );
+            assert!(weaker.noise >= stronger.noise, "Level {}", level + 1);
+            assert!(
+                weaker.blunder_chance >= stronger.blunder_chance,
+                "Level {}",
+                level + 1
+            );
+            assert_ne!(weaker, stronger);
+        }
+
+        let games = 6;
+        for &(stronger, weaker) in &[(4, 1), (8, 4)] {
+            let points = points(stronger, weaker, games);
+            assert!(
+                points > games as f64,
+                "Level {} only scored {} against level {}",
+                stronger,
+                points,
+                weaker
+            );
+        }
+    }
+
+    #[test]
+    fn time_limit_returns_a_move() {
+        let player_one = MinimaxPlayer::new('X').with_limits(SearchLimits {
+            depth: None,
+            time: Some(Duration::from_millis(50)),
+        });
+        let player_two = MinimaxPlayer::new('O');
+        let game = Othello::with_players(&player_one, &player_two, 8, 8);
+
+        let info = player_one.search(&game);
+        assert!(!info.exact);
+        assert!(game.successors('X').contains(&info.best_move.unwrap()));
+        assert!(info.elapsed < Duration::from_secs(5));
     }
 }
@@ -2,10 +2,12 @@
 
 use crate::Othello;
 
+pub mod external;
 pub mod human;
 pub mod minimax;
 #[cfg(feature = "with_random")]
 pub mod random;
+pub mod spec;
 #[cfg(test)]
 pub mod specific;
 
@@ -19,4 +21,16 @@ pub trait Player {
 
     /// Returns the game move chosen by the player.
     fn get_move(&self, board: &Othello) -> (usize, usize);
+
+    /// Whether an invalid move from this player loses the game instead of being asked for again.
+    ///
+    /// Players that can fail in ways they can't recover from (like a crashed subprocess) should forfeit.
+    fn forfeits_illegal_moves(&self) -> bool {
+        false
+    }
+
+    /// Whether to take over the opponent's position when the swap rule offers it, right after their first move.
+    fn wants_swap(&self, _game: &Othello) -> bool {
+        false
+    }
 }
@@ -0,0 +1,293 @@
+//! A container module for player specifications
+//!
+//! A spec is a description of a player that can be parsed from the command line and built as many times as
+//! needed, for example once per game on each worker thread.
+//!
+//! The format is the player type, optionally followed by a colon and comma separated options:
+//! `human`, `minimax`, `minimax:3`, `minimax:depth=4`, `minimax:depth=6,weights=weights.txt,think`.
+//! Minimax players also take a per-move time limit in milliseconds (`time=500`) and a `seed` for the randomness of
+//! difficulty levels.
+//!
+//! A bare number is a minimax difficulty level from 1 to 10 (see `Difficulty`).
+//!
+//! External engines take the command to run and an optional per-move timeout in milliseconds:
+//! `external:cmd=./my-engine --fast,timeout=2000`. The command is split on whitespace.
+
+use crate::eval::pattern::PatternEval;
+use crate::player::external::{ExternalPlayer, DEFAULT_TIMEOUT};
+use crate::player::human::HumanPlayer;
+use crate::player::minimax::{Difficulty, MinimaxPlayer, SearchLimits};
+use crate::player::Player;
+
+#[cfg(feature = "with_random")]
+use crate::player::random::RandomPlayer;
+
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PlayerKind {
+    Human,
+    #[cfg(feature = "with_random")]
+    Random,
+    Minimax,
+    External,
+}
+
+impl PlayerKind {
+    /// Whether this player moves without user input.
+    pub fn is_bot(self) -> bool {
+        self != PlayerKind::Human
+    }
+}
+
+/// A buildable description of a player.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerSpec {
+    pub kind: PlayerKind,
+    /// The difficulty level (1 to 10) of minimax players.
+    pub level: Option<u8>,
+    /// The search depth limit for minimax players. Overrides the depth of the level.
+    pub depth: Option<usize>,
+    /// A pattern weight file minimax players use to score positions at the depth limit.
+    pub weights: Option<PathBuf>,
+    /// Whether minimax players print search information while they think.
+    pub think: bool,
+    /// How long minimax players search for each move.
+    pub time: Option<Duration>,
+    /// Seeds minimax players so games can be replayed.
+    pub seed: Option<u64>,
+    /// The command line that starts an external engine.
+    pub command: Option<String>,
+    /// How long external engines get for each move.
+    pub timeout: Option<Duration>,
+}
+
+impl PlayerSpec {
+    pub fn new(kind: PlayerKind) -> Self {
+        Self {
+            kind,
+            level: None,
+            depth: None,
+            weights: None,
+            think: false,
+            time: None,
+            seed: None,
+            command: None,
+            timeout: None,
+        }
+    }
+
+    /// Creates the player described by this spec.
+    pub fn build(&self, symbol: char) -> Result<Box<dyn Player>, String> {
+        Ok(match self.kind {
+            PlayerKind::Human => Box::new(HumanPlayer(symbol)),
+            #[cfg(feature = "with_random")]
+            PlayerKind::Random => Box::new(RandomPlayer(symbol)),
+            PlayerKind::Minimax => Box::new(self.build_minimax(symbol)?),
+            PlayerKind::External => Box::new(self.build_external(symbol)?),
+        })
+    }
+
+    /// Creates a minimax player with this spec's options, whatever kind of player the spec is for.
+    ///
+    /// Useful for searching on behalf of a player, such as giving hints.
+    pub fn build_minimax(&self, symbol: char) -> Result<MinimaxPlayer, String> {
+        let mut player = MinimaxPlayer::new(symbol).with_thinking(self.think);
+        if let Some(level) = self.level {
+            player = player.with_level(level);
+        }
+        if let Some(depth) = self.depth {
+            player = player.with_depth(depth);
+        }
+        if let Some(time) = self.time {
+            let limits = SearchLimits {
+                time: Some(time),
+                ..player.limits()
+            };
+            player = player.with_limits(limits);
+        }
+        if let Some(seed) = self.seed {
+            player = player.with_seed(seed);
+        }
+        if let Some(path) = &self.weights {
+            let evaluator = PatternEval::from_file(path)
+                .map_err(|err| format!("Couldn't load weights from {}: {}", path.display(), err))?;
+            player = player.with_evaluator(evaluator);
+        }
+        Ok(player)
+    }
+
+    /// Starts the external engine this spec describes.
+    pub fn build_external(&self, symbol: char) -> Result<ExternalPlayer, String> {
+        let command = self.command.as_deref().unwrap_or("");
+        let mut words = command.split_whitespace().map(str::to_string);
+        let program = words.next().ok_or_else(|| {
+            "External players need a command, like `external:cmd=./engine`".to_string()
+        })?;
+        let args = words.collect::<Vec<_>>();
+        ExternalPlayer::launch(
+            symbol,
+            &program,
+            &args,
+            self.timeout.unwrap_or(DEFAULT_TIMEOUT),
+        )
+        .map_err(|err| format!("Couldn't start `{}`: {}", command, err))
+    }
+}
+
+impl FromStr for PlayerSpec {
+    type Err = String;
+
+    /// # Parses a player spec
+    ///
+    /// ```
+    /// # use othlib::player::spec::{PlayerKind, PlayerSpec};
+    /// let spec = "minimax:depth=4".parse::<PlayerSpec>().unwrap();
+    /// assert_eq!(PlayerKind::Minimax, spec.kind);
+    /// assert_eq!(Some(4), spec.depth);
+    /// assert_eq!(Some(3), "minimax:3".parse::<PlayerSpec>().unwrap().level);
+    /// assert!("minimax:11".parse::<PlayerSpec>().is_err());
+    /// assert!("minimax:depth=four".parse::<PlayerSpec>().is_err());
+    /// let spec = "minimax:5,time=250,seed=7".parse::<PlayerSpec>().unwrap();
+    /// assert_eq!("minimax:5,time=250,seed=7", spec.to_string());
+    /// assert!("robot".parse::<PlayerSpec>().is_err());
+    ///
+    /// let spec = "external:cmd=./engine --fast,timeout=500".parse::<PlayerSpec>().unwrap();
+    /// assert_eq!(Some("./engine --fast"), spec.command.as_deref());
+    /// assert!("external".parse::<PlayerSpec>().is_err());
+    /// ```
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.splitn(2, ':');
+        let kind = match parts.next().unwrap_or("") {
+            "human" => PlayerKind::Human,
+            #[cfg(feature = "with_random")]
+            "random" => PlayerKind::Random,
+            "minimax" => PlayerKind::Minimax,
+            "external" => PlayerKind::External,
+            _ => return Err(format!("Possible player types are {}", kind_names())),
+        };
+
+        let mut spec = Self::new(kind);
+        for option in parts.next().into_iter().flat_map(|x| x.split(',')) {
+            let mut pair = option.splitn(2, '=');
+            match (pair.next().unwrap_or(""), pair.next()) {
+                (level, None) | ("level", Some(level))
+                    if kind == PlayerKind::Minimax && level.parse::<usize>().is_ok() =>
+                {
+                    spec.level = Some(parse_level(level)?);
+                }
+                ("depth", Some(depth)) if kind == PlayerKind::Minimax => {
+                    spec.depth = Some(
+                        depth
+                            .parse()
+                            .map_err(|_| format!("`{}` is not a valid depth", depth))?,
+                    );
+                }
+                ("weights", Some(path)) if kind == PlayerKind::Minimax => {
+                    spec.weights = Some(PathBuf::from(path));
+                }
+                ("think", None) if kind == PlayerKind::Minimax => spec.think = true,
+                ("time", Some(time)) if kind == PlayerKind::Minimax => {
+                    let millis = time
+                        .parse()
+                        .map_err(|_| format!("`{}` is not a valid time", time))?;
+                    spec.time = Some(Duration::from_millis(millis));
+                }
+                ("seed", Some(seed)) if kind == PlayerKind::Minimax => {
+                    spec.seed = Some(
+                        seed.parse()
+                            .map_err(|_| format!("`{}` is not a valid seed", seed))?,
+                    );
+                }
+                ("cmd", Some(command)) if kind == PlayerKind::External => {
+                    spec.command = Some(command.to_string());
+                }
+                ("timeout", Some(timeout)) if kind == PlayerKind::External => {
+                    let millis = timeout
+                        .parse()
+                        .map_err(|_| format!("`{}` is not a valid timeout", timeout))?;
+                    spec.timeout = Some(Duration::from_millis(millis));
+                }
+                _ => return Err(format!("Unknown option `{}` for `{}`", option, text)),
+            }
+        }
+
+        if kind == PlayerKind::External && spec.command.is_none() {
+            return Err(
+                "External players need a command, like `external:cmd=./engine`".to_string(),
+            );
+        }
+
+        Ok(spec)
+    }
+}
+
+impl fmt::Display for PlayerSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self.kind {
+            PlayerKind::Human => "human",
+            #[cfg(feature = "with_random")]
+            PlayerKind::Random => "random",
+            PlayerKind::Minimax => "minimax",
+            PlayerKind::External => "external",
+        };
+        let mut options = vec![];
+        if let Some(level) = self.level {
+            options.push(level.to_string());
+        }
+        if let Some(depth) = self.depth {
+            options.push(format!("depth={}", depth));
+        }
+        if let Some(path) = &self.weights {
+            options.push(format!("weights={}", path.display()));
+        }
+        if let Some(time) = self.time {
+            options.push(format!("time={}", time.as_millis()));
+        }
+        if let Some(seed) = self.seed {
+            options.push(format!("seed={}", seed));
+        }
+        if self.think {
+            options.push("think".to_string());
+        }
+        if let Some(command) = &self.command {
+            options.push(format!("cmd={}", command));
+        }
+        if let Some(timeout) = self.timeout {
+            options.push(format!("timeout={}", timeout.as_millis()));
+        }
+
+        if options.is_empty() {
+            write!(f, "{}", name)
+        } else {
+            write!(f, "{}:{}", name, options.join(","))
+        }
+    }
+}
+
+fn parse_level(level: &str) -> Result<u8, String> {
+    level
+        .parse::<u8>()
+        .ok()
+        .filter(|level| (Difficulty::MIN_LEVEL..=Difficulty::MAX_LEVEL).contains(level))
+        .ok_or_else(|| {
+            format!(
+                "`{}` is not a level, levels go from {} to {}",
+                level,
+                Difficulty::MIN_LEVEL,
+                Difficulty::MAX_LEVEL
+            )
+        })
+}
+
+/// Lists the player types compiled into this build.
+pub fn kind_names() -> &'static str {
+    if cfg!(feature = "with_random") {
+        "`human`, `random`, `minimax`, and `external`"
+    } else {
+        "`human`, `minimax`, and `external`"
+    }
+}
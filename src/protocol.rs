@@ -0,0 +1,259 @@
+//! A minimal line protocol for running players in another process.
+//!
+//! The host sends one request per line and the engine answers each `position` request with exactly one line:
+//!
+//! * `position <board> <side> <opponent> [rules]`: answered with `move <row> <col>`, the move `side` plays, or `pass`
+//! * `quit`: the engine should exit
+//!
+//! `board` is `Board::encode`d, and `side` and `opponent` are the symbols of the player to move and the other
+//! player. Rows and columns are counted the same way as everywhere else in othlib, starting from the bottom left.
+//! The rules that aren't the defaults follow as `variant=`, `topology=`, `setup=`, `grid=` and `captures=` words,
+//! like `variant=misere grid=hex`, so engines play the same game as the host.
+//! Engines can be written in any language, `ExternalPlayer` runs them and `serve` turns any bot into one.
+
+use crate::board::Board;
+use crate::player::spec::PlayerSpec;
+use crate::rules::Rules;
+use crate::ActivePlayer;
+
+use std::io::{self, BufRead, Write};
+
+/// A request from the host.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Request {
+    Position {
+        board: Board,
+        side: char,
+        opponent: char,
+        /// The rules `side` moves under, see `Rules::of_game`.
+        rules: Rules,
+    },
+    Quit,
+}
+
+impl Request {
+    /// # Parses a request line
+    ///
+    /// ```
+    /// # use othlib::protocol::Request;
+    /// # use othlib::rules::{Rules, Variant};
+    /// match Request::parse("position ..../.XO./.OX./.... X O variant=misere") {
+    ///     Some(Request::Position { board, side, opponent, rules }) => {
+    ///         assert_eq!(Some('O'), board.get_cell(1, 2));
+    ///         assert_eq!(('X', 'O'), (side, opponent));
+    ///         assert_eq!(Rules { variant: Variant::Misere, ..Rules::default() }, rules);
+    ///     }
+    ///     other => panic!("{:?}", other),
+    /// }
+    /// assert_eq!(Some(Request::Quit), Request::parse("quit"));
+    /// assert_eq!(None, Request::parse("position ..../.XO X O"));
+    /// assert_eq!(None, Request::parse("position ..../.XO./.OX./.... X O grid=round"));
+    /// ```
+    pub fn parse(line: &str) -> Option<Self> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["quit"] => Some(Request::Quit),
+            ["position", board, side, opponent, rules @ ..] => Some(Request::Position {
+                board: Board::decode(board)?,
+                side: single_char(side)?,
+                opponent: single_char(opponent)?,
+                rules: parse_rules(rules)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn parse_rules(words: &[&str]) -> Option<Rules> {
+    let mut rules = Rules::default();
+    for word in words {
+        match word.split_once('=')? {
+            ("variant", value) => rules.variant = value.parse().ok()?,
+            ("topology", value) => rules.topology = value.parse().ok()?,
+            ("setup", value) => rules.setup = value.parse().ok()?,
+            ("grid", value) => rules.grid = value.parse().ok()?,
+            ("captures", value) => rules.captures = value.parse().ok()?,
+            _ => return None,
+        }
+    }
+    Some(rules)
+}
+
+impl std::fmt::Display for Request {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Request::Position {
+                board,
+                side,
+                opponent,
+                rules,
+            } => {
+                write!(f, "position {} {} {}", board.encode(), side, opponent)?;
+                let default = Rules::default();
+                if rules.variant != default.variant {
+                    write!(f, " variant={}", rules.variant)?;
+                }
+                if rules.topology != default.topology {
+                    write!(f, " topology={}", rules.topology)?;
+                }
+                if rules.setup != default.setup {
+                    write!(f, " setup={}", rules.setup)?;
+                }
+                if rules.grid != default.grid {
+                    write!(f, " grid={}", rules.grid)?;
+                }
+                if rules.captures != default.captures {
+                    write!(f, " captures={}", rules.captures)?;
+                }
+                Ok(())
+            }
+            Request::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+/// # Parses an engine's answer to a `position` request
+///
+/// Returns `Some(None)` for a pass and `None` if the line isn't a valid answer.
+///
+/// ```
+/// # use othlib::protocol::parse_reply;
+/// assert_eq!(Some(Some((2, 3))), parse_reply("move 2 3"));
+/// assert_eq!(Some(None), parse_reply("pass"));
+/// assert_eq!(None, parse_reply("move d3"));
+/// ```
+pub fn parse_reply(line: &str) -> Option<Option<(usize, usize)>> {
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    match words.as_slice() {
+        ["pass"] => Some(None),
+        ["move", row, col] => Some(Some((row.parse().ok()?, col.parse().ok()?))),
+        _ => None,
+    }
+}
+
+/// Formats an answer to a `position` request.
+pub fn format_reply(coords: Option<(usize, usize)>) -> String {
+    match coords {
+        Some((row, col)) => format!("move {} {}", row, col),
+        None => "pass".to_string(),
+    }
+}
+
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(symbol), None) => Some(symbol),
+        _ => None,
+    }
+}
+
+/// Answers requests from `input` with moves chosen by the player `spec` describes, until `quit` or the input ends.
+///
+/// Lines that aren't valid requests are answered with `error <reason>` so the host isn't left waiting.
+pub fn serve<R: BufRead, W: Write>(spec: &PlayerSpec, input: R, mut out: W) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        match Request::parse(&line) {
+            Some(Request::Quit) => break,
+            Some(Request::Position {
+                board,
+                side,
+                opponent,
+                rules,
+            }) => match choose_move(spec, board, side, opponent, &rules) {
+                Ok(coords) => writeln!(out, "{}", format_reply(coords))?,
+                Err(err) => writeln!(out, "error {}", err)?,
+            },
+            None if line.trim().is_empty() => continue,
+            None => writeln!(out, "error Unknown request `{}`", line)?,
+        }
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Asks the player `spec` describes for `side`'s move on `board` under `rules`. Returns `None` if `side` has to pass.
+pub fn choose_move(
+    spec: &PlayerSpec,
+    board: Board,
+    side: char,
+    opponent: char,
+    rules: &Rules,
+) -> Result<Option<(usize, usize)>, String> {
+    let (player, other) = (spec.build(side)?, spec.build(opponent)?);
+    let mut game = rules.resume(&*player, &*other, board, ActivePlayer::PlayerOne);
+    if !game.player_has_more_moves(ActivePlayer::PlayerOne) {
+        return Ok(None);
+    }
+    Ok(Some(game.get_move()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::player::spec::PlayerKind;
+    use crate::rules::{Captures, Grid, Setup, Topology, Variant};
+
+    #[test]
+    fn serves_legal_moves() {
+        let spec = PlayerSpec {
+            depth: Some(2),
+            ..PlayerSpec::new(PlayerKind::Minimax)
+        };
+        let input = "position ..../.XO./.OX./.... X O\n\nposition XXXX/XXXX/XXXX/XXX. O X\nbad\nquit\nposition ..../.XO./.OX./.... O X\n";
+        let mut out = vec![];
+        serve(&spec, input.as_bytes(), &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(3, lines.len());
+        let first = parse_reply(lines[0]).unwrap().unwrap();
+        assert!([(0, 2), (1, 3), (2, 0), (3, 1)].contains(&first));
+        assert_eq!(Some(None), parse_reply(lines[1]));
+        assert!(lines[2].starts_with("error"));
+    }
+
+    #[test]
+    fn serves_moves_under_the_rules_sent() {
+        let spec = PlayerSpec {
+            depth: Some(2),
+            ..PlayerSpec::new(PlayerKind::Minimax)
+        };
+        let input = "position ..../..../..../..XO X O
+position ..../..../..../..XO X O topology=torus
+";
+        let mut out = vec![];
+        serve(&spec, input.as_bytes(), &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(Some(None), parse_reply(lines[0]));
+        let (row, col) = parse_reply(lines[1]).unwrap().unwrap();
+        assert_eq!(0, col);
+        assert!(
+            Board::decode("..../..../..../..XO")
+                .unwrap()
+                .get_cell(row, 2)
+                == Some('X')
+        );
+    }
+
+    #[test]
+    fn rules_survive_the_request_line() {
+        let rules = Rules {
+            variant: Variant::Misere,
+            topology: Topology::Torus,
+            setup: Setup::Free,
+            grid: Grid::Hex,
+            captures: Captures::Diagonal,
+            ..Rules::default()
+        };
+        let request = Request::Position {
+            board: Board::decode("..../.XO./.OX./....").unwrap(),
+            side: 'X',
+            opponent: 'O',
+            rules,
+        };
+        assert_eq!(Some(request.clone()), Request::parse(&request.to_string()));
+    }
+}
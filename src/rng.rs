@@ -0,0 +1,53 @@
+//! A tiny seeded random number generator.
+//!
+//! `rand` is an optional dependency, but opening randomization needs reproducible randomness in every build.
+//! This is xorshift64*, which is plenty for shuffling games and is never used for anything security related.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// Creates a generator from a seed. The same seed always produces the same sequence.
+    pub fn with_seed(seed: u64) -> Self {
+        // Mix the seed so small seeds (0, 1, 2...) don't start out correlated, and never hold a zero state
+        let mut rng = Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1);
+        rng.next_u64();
+        rng
+    }
+
+    /// Creates a generator seeded from the clock.
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or(0);
+        Self::with_seed(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a number in `0..bound`. `bound` must not be 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use othlib::rng::Rng;
+    /// let mut rng = Rng::with_seed(7);
+    /// assert!((0..100).all(|_| rng.below(3) < 3));
+    /// ```
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Returns a number in `0.0..1.0`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -0,0 +1,449 @@
+//! Rolit: Othello for two to four players.
+//!
+//! Every color starts with one disc in the middle of the board, even the colors nobody is playing, and the players
+//! take turns in order. A move has to capture if the player has any capturing move. Otherwise it can go on any
+//! empty cell next to a disc (in any of the eight directions). Captures work like Othello's, except a line can run
+//! over discs of every other color. Since there's always somewhere to play until the board is full, nobody ever
+//! passes, and whoever has the most discs at the end wins.
+//!
+//! `Othello` stays a two player game, so Rolit has its own game and player trait. `search` has players that look
+//! ahead for more than two players.
+
+use crate::board::Board;
+use crate::player::human::HumanPlayer;
+use crate::rules::Grid;
+use crate::Direction;
+
+use std::fmt::{self, Write};
+
+pub mod search;
+
+use search::{MaxNPlayer, Strategy};
+
+/// The colors, in turn order. Players take them from the start of the list.
+pub const COLORS: [char; 4] = ['R', 'Y', 'G', 'B'];
+
+/// # Models required functions for a Rolit player.
+///
+/// Like `Player`, `get_move` must return legal moves or the game will keep asking.
+pub trait RolitPlayer {
+    /// Returns the player symbol.
+    fn get_symbol(&self) -> char;
+
+    /// Returns the game move chosen by the player.
+    fn get_move(&self, game: &Rolit) -> (usize, usize);
+}
+
+/// # Builds a player from the command line
+///
+/// Players are `human`, `maxn[:DEPTH]` or `paranoid[:DEPTH]`.
+///
+/// ```
+/// # use othlib::rolit::player_from_spec;
+/// assert_eq!('Y', player_from_spec("paranoid:2", 'Y').unwrap().get_symbol());
+/// assert!(player_from_spec("minimax", 'R').is_err());
+/// assert!(player_from_spec("maxn:x", 'R').is_err());
+/// ```
+pub fn player_from_spec(text: &str, symbol: char) -> Result<Box<dyn RolitPlayer>, String> {
+    let (kind, depth) = text.split_once(':').unwrap_or((text, ""));
+    let strategy = match kind {
+        "human" if depth.is_empty() => return Ok(Box::new(HumanPlayer(symbol))),
+        "maxn" => Strategy::MaxN,
+        "paranoid" => Strategy::Paranoid,
+        _ => {
+            return Err(format!(
+                "`{}` isn't a Rolit player, the players are `human`, `maxn[:DEPTH]` and `paranoid[:DEPTH]`",
+                text
+            ))
+        }
+    };
+    let mut player = MaxNPlayer::new(symbol, strategy);
+    if !depth.is_empty() {
+        match depth.parse::<usize>() {
+            Ok(depth) if depth > 0 => player = player.with_depth(depth),
+            _ => return Err(format!("`{}` isn't a search depth.", depth)),
+        }
+    }
+    Ok(Box::new(player))
+}
+
+/// # Rolit game implementation
+///
+/// Accepts two to four players, who move in the order they're given.
+#[derive(Clone)]
+pub struct Rolit<'a> {
+    players: Vec<&'a dyn RolitPlayer>,
+    active: usize,
+    board: Board,
+}
+
+impl<'a> Rolit<'a> {
+    /// # Creates a Rolit game
+    ///
+    /// The four middle cells get one disc of each player's color, going clockwise, then the colors from `COLORS`
+    /// that nobody has.
+    ///
+    /// ```
+    /// # use othlib::player::human::HumanPlayer;
+    /// # use othlib::rolit::{Rolit, RolitPlayer};
+    /// let players = [HumanPlayer('R'), HumanPlayer('Y'), HumanPlayer('G')];
+    /// let game = Rolit::with_players(players.iter().map(|x| x as &dyn RolitPlayer).collect(), 4, 4).unwrap();
+    /// assert_eq!("..../.BG./.RY./....", game.board().encode());
+    /// ```
+    pub fn with_players(
+        players: Vec<&'a dyn RolitPlayer>,
+        rows: usize,
+        cols: usize,
+    ) -> Result<Self, String> {
+        if !(2..=COLORS.len()).contains(&players.len()) {
+            return Err(format!(
+                "Rolit is for 2 to {} players, not {}.",
+                COLORS.len(),
+                players.len()
+            ));
+        }
+        let mut colors = players.iter().map(|x| x.get_symbol()).collect::<Vec<_>>();
+        for (index, &symbol) in colors.iter().enumerate() {
+            if colors[..index].contains(&symbol) {
+                return Err(format!("Two players can't both be {}.", symbol));
+            }
+        }
+        for color in COLORS {
+            if colors.len() < COLORS.len() && !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+
+        let (rows, cols) = Grid::Square.board_size(rows, cols)?;
+        let mut board = Board::with_size(rows, cols);
+        let middle = Grid::Square.middle(rows, cols).into_iter().rev();
+        for ((row, col), &color) in middle.zip(&colors) {
+            board.set_cell(row, col, color);
+        }
+        Self::with_board(players, board, 0)
+    }
+
+    /// Resumes a game from a position, with player `active` to move.
+    pub fn with_board(
+        players: Vec<&'a dyn RolitPlayer>,
+        board: Board,
+        active: usize,
+    ) -> Result<Self, String> {
+        if !(2..=COLORS.len()).contains(&players.len()) {
+            return Err(format!(
+                "Rolit is for 2 to {} players, not {}.",
+                COLORS.len(),
+                players.len()
+            ));
+        }
+        if active >= players.len() {
+            return Err(format!("There's no player {} to move.", active + 1));
+        }
+        Ok(Self {
+            players,
+            active,
+            board,
+        })
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// The number of players.
+    pub fn players(&self) -> usize {
+        self.players.len()
+    }
+
+    /// The index of the player to move.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn symbol(&self, player: usize) -> char {
+        self.players[player].get_symbol()
+    }
+
+    pub fn get_active_symbol(&self) -> char {
+        self.symbol(self.active)
+    }
+
+    /// The index of the player with `symbol`, if anyone has it.
+    pub fn player_from_symbol(&self, symbol: char) -> Option<usize> {
+        self.players.iter().position(|x| x.get_symbol() == symbol)
+    }
+
+    /// How many discs `symbol` playing at `row`, `col` would capture in `direction`.
+    fn captures(&self, row: usize, col: usize, symbol: char, direction: Direction) -> usize {
+        let mut captured = 0;
+        let mut next = direction.new_coords_from_direction(row, col);
+        while let Some((row, col)) = next.filter(|&(row, col)| self.board.is_in_bounds(row, col)) {
+            match self.board.get_cell(row, col) {
+                Some(disc) if disc == symbol => return captured,
+                Some(_) => captured += 1,
+                None => return 0,
+            }
+            next = direction.new_coords_from_direction(row, col);
+        }
+        0
+    }
+
+    fn is_capture(&self, row: usize, col: usize, symbol: char) -> bool {
+        Direction::cardinals().any(|direction| self.captures(row, col, symbol, direction) > 0)
+    }
+
+    fn is_next_to_a_disc(&self, row: usize, col: usize) -> bool {
+        Direction::cardinals().any(|direction| {
+            direction
+                .new_coords_from_direction(row, col)
+                .filter(|&(row, col)| self.board.is_in_bounds(row, col))
+                .is_some_and(|(row, col)| !self.board.is_cell_empty(row, col))
+        })
+    }
+
+    /// # The moves `symbol` can play
+    ///
+    /// Captures if there are any, otherwise every empty cell next to a disc.
+    ///
+    /// ```
+    /// # use othlib::player::human::HumanPlayer;
+    /// # use othlib::rolit::{Rolit, RolitPlayer};
+    /// let players = [HumanPlayer('R'), HumanPlayer('Y'), HumanPlayer('G'), HumanPlayer('B')];
+    /// let game = Rolit::with_players(players.iter().map(|x| x as &dyn RolitPlayer).collect(), 4, 4).unwrap();
+    /// // Every color is on the board, so there are captures from the start
+    /// assert_eq!(vec![(0, 1), (0, 3), (2, 3)], game.successors('R'));
+    /// ```
+    pub fn successors(&self, symbol: char) -> Vec<(usize, usize)> {
+        let empty = (0..self.board.rows())
+            .flat_map(|row| (0..self.board.cols()).map(move |col| (row, col)))
+            .filter(|&(row, col)| {
+                self.board.is_in_bounds(row, col) && self.board.is_cell_empty(row, col)
+            })
+            .collect::<Vec<_>>();
+        let captures = empty
+            .iter()
+            .copied()
+            .filter(|&(row, col)| self.is_capture(row, col, symbol))
+            .collect::<Vec<_>>();
+        if !captures.is_empty() {
+            return captures;
+        }
+        empty
+            .into_iter()
+            .filter(|&(row, col)| self.is_next_to_a_disc(row, col))
+            .collect()
+    }
+
+    pub fn is_legal_move(&self, row: usize, col: usize, symbol: char) -> bool {
+        self.board.is_in_bounds(row, col) && self.successors(symbol).contains(&(row, col))
+    }
+
+    /// The game ends when nobody can place a disc. Everyone can play next to a disc, so that only happens once there
+    /// are no empty cells next to one.
+    pub fn has_more_moves(&self) -> bool {
+        !self.successors(self.get_active_symbol()).is_empty()
+    }
+
+    /// Places a disc and flips what it captures, then passes the turn to the player after the mover. Doesn't check
+    /// that the move is legal.
+    pub fn play_move(&mut self, row: usize, col: usize, symbol: char) {
+        for direction in Direction::cardinals() {
+            let captured = self.captures(row, col, symbol, direction);
+            let mut cell = (row, col);
+            for _ in 0..captured {
+                cell = direction
+                    .new_coords_from_direction(cell.0, cell.1)
+                    .expect("Captured discs are on the board");
+                self.board.set_cell(cell.0, cell.1, symbol);
+            }
+        }
+        self.board.set_cell(row, col, symbol);
+        if let Some(player) = self.player_from_symbol(symbol) {
+            self.active = (player + 1) % self.players.len();
+        }
+    }
+
+    /// Plays a move for the active player, refusing moves that aren't legal.
+    pub fn try_move(&mut self, row: usize, col: usize) -> Result<(), String> {
+        let symbol = self.get_active_symbol();
+        if !self.is_legal_move(row, col, symbol) {
+            return Err(format!(
+                "Row {}, Col {} isn't a legal move for {}.",
+                row, col, symbol
+            ));
+        }
+        self.play_move(row, col, symbol);
+        Ok(())
+    }
+
+    /// Each player's disc count, in turn order.
+    pub fn scores(&self) -> Vec<usize> {
+        let counts = self.board.char_counts();
+        (0..self.players())
+            .map(|player| *counts.get(&self.symbol(player)).unwrap_or(&0))
+            .collect()
+    }
+
+    /// The players with the most discs. More than one is a tie.
+    pub fn leaders(&self) -> Vec<usize> {
+        let scores = self.scores();
+        let best = scores.iter().max().copied().unwrap_or(0);
+        (0..self.players())
+            .filter(|&player| scores[player] == best)
+            .collect()
+    }
+
+    pub fn get_winner(&self) -> Option<String> {
+        if self.has_more_moves() {
+            return None;
+        }
+        let leaders = self.leaders();
+        let discs = self.scores()[leaders[0]];
+        let names = leaders
+            .iter()
+            .map(|player| (player + 1).to_string())
+            .collect::<Vec<_>>();
+        match &names[..] {
+            [winner] => Some(format!("Player {} wins with {} discs!", winner, discs)),
+            [rest @ .., last] => Some(format!(
+                "Players {} and {} tie with {} discs!",
+                rest.join(", "),
+                last,
+                discs
+            )),
+            [] => unreachable!("Someone always has the most discs"),
+        }
+    }
+
+    /// Plays one turn, printing the board and the chosen move when `verbose`.
+    pub fn take_turn(&mut self, verbose: bool) {
+        let symbol = self.get_active_symbol();
+        if verbose {
+            println!("{}", self);
+            println!("Player {} ({}) move:", self.active + 1, symbol);
+        }
+        loop {
+            let (row, col) = self.players[self.active].get_move(self);
+            match self.try_move(row, col) {
+                Ok(()) => {
+                    if verbose {
+                        println!("[Selected] Row: {}, Col: {}", row, col);
+                    }
+                    return;
+                }
+                Err(_) if verbose => println!("Invalid move."),
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// Plays the game to the end and returns the players with the most discs.
+    pub fn run(&mut self, verbose: bool) -> Vec<usize> {
+        while self.has_more_moves() {
+            self.take_turn(verbose);
+        }
+        self.leaders()
+    }
+}
+
+impl<'a> fmt::Display for Rolit<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut build = String::from("\n");
+        for (player, score) in self.scores().into_iter().enumerate() {
+            writeln!(
+                build,
+                "Player {} ({}) score: {}",
+                player + 1,
+                self.symbol(player),
+                score
+            )?;
+        }
+        writeln!(build)?;
+
+        let successors = self.successors(self.get_active_symbol());
+        for row in (0..self.board.rows()).rev() {
+            write!(build, "{}:|", row)?;
+            for col in 0..self.board.cols() {
+                let cell = match self.board.get_cell(row, col) {
+                    Some(symbol) => symbol,
+                    None if successors.contains(&(row, col)) => '?',
+                    None => '.',
+                };
+                write!(build, " {}", cell)?;
+            }
+            writeln!(build)?;
+        }
+        writeln!(build, "   {}", "-".repeat((self.board.cols() * 2) + 1))?;
+        write!(build, "   ")?;
+        for col in 0..self.board.cols() {
+            write!(build, " {}", col)?;
+        }
+        writeln!(build)?;
+
+        write!(f, "{}", build)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn captures_come_first() {
+        let players = [HumanPlayer('R'), HumanPlayer('Y'), HumanPlayer('G')];
+        let players = players
+            .iter()
+            .map(|x| x as &dyn RolitPlayer)
+            .collect::<Vec<_>>();
+        let board = Board::decode("RG../..../.Y../....").unwrap();
+        let mut game = Rolit::with_board(players.clone(), board, 0).unwrap();
+
+        // R can capture the G, so nothing else is allowed
+        assert_eq!(vec![(0, 2)], game.successors('R'));
+        assert!(game.try_move(1, 0).is_err());
+        game.try_move(0, 2).unwrap();
+        assert_eq!("RRR./..../.Y../....", game.board().encode());
+        assert_eq!(1, game.active());
+
+        // Y can't capture anything, so it places next to any disc
+        assert_eq!(
+            vec![
+                (0, 3),
+                (1, 0),
+                (1, 1),
+                (1, 2),
+                (1, 3),
+                (2, 0),
+                (2, 2),
+                (3, 0),
+                (3, 1),
+                (3, 2)
+            ],
+            game.successors('Y')
+        );
+        game.try_move(3, 1).unwrap();
+        assert_eq!(vec![3, 2, 0], game.scores());
+        assert_eq!(2, game.active());
+        assert!(Rolit::with_board(players, Board::with_size(4, 4), 3).is_err());
+    }
+
+    #[test]
+    fn searches_fill_the_board() {
+        let players = [
+            MaxNPlayer::new('R', Strategy::MaxN).with_depth(2),
+            MaxNPlayer::new('Y', Strategy::Paranoid).with_depth(2),
+            MaxNPlayer::new('G', Strategy::MaxN).with_depth(1),
+            MaxNPlayer::new('B', Strategy::Paranoid).with_depth(1),
+        ];
+        let mut game = Rolit::with_players(
+            players.iter().map(|x| x as &dyn RolitPlayer).collect(),
+            6,
+            6,
+        )
+        .unwrap();
+        let leaders = game.run(false);
+        assert_eq!(36, game.scores().iter().sum::<usize>());
+        assert!(!leaders.is_empty());
+        assert!(game.get_winner().is_some());
+    }
+}
@@ -0,0 +1,169 @@
+//! Search players for Rolit.
+//!
+//! Minimax needs a zero-sum game between two sides, which Rolit with more players isn't. Max-n gives every player
+//! their own score and lets each one pick the move that's best for them. Paranoid search assumes everyone else is
+//! working together against the searching player, which turns the game back into two sides, so it can use
+//! alpha-beta pruning and look further ahead in the same time.
+//!
+//! Positions are scored by each player's lead in discs over the best of the other players.
+
+use crate::rolit::{Rolit, RolitPlayer};
+
+/// How a search player expects the other players to move.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Strategy {
+    /// Everyone plays for themselves.
+    MaxN,
+    /// Everyone plays against the searching player.
+    Paranoid,
+}
+
+/// A player that searches a fixed number of moves ahead.
+#[derive(Clone, Debug)]
+pub struct MaxNPlayer {
+    symbol: char,
+    strategy: Strategy,
+    depth: usize,
+}
+
+impl MaxNPlayer {
+    /// Creates a player that searches 3 moves ahead.
+    pub fn new(symbol: char, strategy: Strategy) -> Self {
+        Self {
+            symbol,
+            strategy,
+            depth: 3,
+        }
+    }
+
+    /// How many moves (of any player) to search ahead. At least one.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// # Finds the best move for the active player and what it scores
+    ///
+    /// ```
+    /// # use othlib::player::human::HumanPlayer;
+    /// # use othlib::rolit::search::{MaxNPlayer, Strategy};
+    /// # use othlib::rolit::{Rolit, RolitPlayer};
+    /// let (red, yellow) = (MaxNPlayer::new('R', Strategy::MaxN), HumanPlayer('Y'));
+    /// let game = Rolit::with_players(vec![&red as &dyn RolitPlayer, &yellow], 4, 4).unwrap();
+    /// let (best, _) = red.search(&game);
+    /// assert!(game.successors('R').contains(&best.unwrap()));
+    /// ```
+    pub fn search(&self, game: &Rolit) -> (Option<(usize, usize)>, isize) {
+        let player = game.active();
+        match self.strategy {
+            Strategy::MaxN => {
+                let (best, scores) = self.max_n(game, self.depth);
+                (best, scores[player])
+            }
+            Strategy::Paranoid => self.paranoid(game, self.depth, player, isize::MIN, isize::MAX),
+        }
+    }
+
+    /// Returns the best move for the player to move and every player's score after it.
+    fn max_n(&self, game: &Rolit, depth: usize) -> (Option<(usize, usize)>, Vec<isize>) {
+        if depth == 0 || !game.has_more_moves() {
+            return (None, leads(game));
+        }
+        let player = game.active();
+        let symbol = game.get_active_symbol();
+        let mut best: Option<((usize, usize), Vec<isize>)> = None;
+        for (row, col) in game.successors(symbol) {
+            let mut next = game.clone();
+            next.play_move(row, col, symbol);
+            let (_, scores) = self.max_n(&next, depth - 1);
+            if best
+                .as_ref()
+                .is_none_or(|(_, best)| scores[player] > best[player])
+            {
+                best = Some(((row, col), scores));
+            }
+        }
+        let (best, scores) = best.expect("A game with moves left has successors");
+        (Some(best), scores)
+    }
+
+    /// Returns the best move for the player to move and `root`'s score after it, when everyone but `root` is out to
+    /// get them.
+    fn paranoid(
+        &self,
+        game: &Rolit,
+        depth: usize,
+        root: usize,
+        mut alpha: isize,
+        mut beta: isize,
+    ) -> (Option<(usize, usize)>, isize) {
+        if depth == 0 || !game.has_more_moves() {
+            return (None, leads(game)[root]);
+        }
+        let maximizing = game.active() == root;
+        let symbol = game.get_active_symbol();
+        let mut best = None;
+        for (row, col) in game.successors(symbol) {
+            let mut next = game.clone();
+            next.play_move(row, col, symbol);
+            let (_, score) = self.paranoid(&next, depth - 1, root, alpha, beta);
+            if maximizing && (best.is_none() || score > alpha) {
+                alpha = alpha.max(score);
+                best = Some((row, col));
+            } else if !maximizing && (best.is_none() || score < beta) {
+                beta = beta.min(score);
+                best = Some((row, col));
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        (best, if maximizing { alpha } else { beta })
+    }
+}
+
+/// Each player's disc count minus the most discs any other player has.
+fn leads(game: &Rolit) -> Vec<isize> {
+    let scores = game.scores();
+    (0..scores.len())
+        .map(|player| {
+            let best_other = (0..scores.len())
+                .filter(|&other| other != player)
+                .map(|other| scores[other])
+                .max()
+                .unwrap_or(0);
+            scores[player] as isize - best_other as isize
+        })
+        .collect()
+}
+
+impl RolitPlayer for MaxNPlayer {
+    fn get_symbol(&self) -> char {
+        self.symbol
+    }
+
+    fn get_move(&self, game: &Rolit) -> (usize, usize) {
+        self.search(game)
+            .0
+            .expect("The game asks for moves only when there are some")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::Board;
+    use crate::player::human::HumanPlayer;
+
+    #[test]
+    fn takes_the_bigger_capture() {
+        let yellow = HumanPlayer('Y');
+        for strategy in [Strategy::MaxN, Strategy::Paranoid] {
+            let red = MaxNPlayer::new('R', strategy).with_depth(1);
+            let board = Board::decode("RYY./Y.../..../....").unwrap();
+            let game = Rolit::with_board(vec![&red, &yellow], board, 0).unwrap();
+            assert_eq!(vec![(0, 3), (2, 0)], game.successors('R'));
+            assert_eq!((Some((0, 3)), 3), red.search(&game));
+        }
+    }
+}
@@ -0,0 +1,594 @@
+//! Rules that change how a game is set up, played and won.
+//!
+//! The board's shape, topology, grid and captures change which moves there are and what they flip, and the setup
+//! and layout change where the game starts. A `Variant` only changes the goal, so it's applied to the final disc
+//! differential: `Variant::outcome` turns it into a result where more is better for every variant, which is what
+//! winners are decided by and what the engines maximize.
+//!
+//! `Rules` collects everything about a game apart from its players and size, so the runners that set up many games
+//! (tournaments, matches, self-play) set them all up the same way.
+
+use crate::board::{Board, Mask};
+use crate::player::Player;
+use crate::{ActivePlayer, Direction, Othello, Step};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// # How games are played
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Rules {
+    pub variant: Variant,
+    /// The shape of the board. `None` plays on the whole rectangle.
+    pub mask: Option<Mask>,
+    pub topology: Topology,
+    pub setup: Setup,
+    /// How the discs are arranged when the game starts with them placed.
+    pub layout: Layout,
+    pub grid: Grid,
+    pub captures: Captures,
+    /// Whether the second player can swap after the first move. See `Othello::with_swap_rule`.
+    pub swap: bool,
+}
+
+impl Rules {
+    /// # Sets up a game between two players
+    ///
+    /// Fails for sizes the grid doesn't fit, like boards less than 2 cells across, and for rules that don't go
+    /// together.
+    ///
+    /// ```
+    /// # use othlib::board::Mask;
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::rules::{Rules, Variant};
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let rules = Rules {
+    ///     variant: Variant::Misere,
+    ///     mask: Some(Mask::octagon(8, 8)),
+    ///     ..Rules::default()
+    /// };
+    /// let game = rules.start(&one, &two, 8, 8).unwrap();
+    /// assert_eq!(Variant::Misere, game.variant());
+    /// assert!(game.board().is_blocked(0, 0));
+    /// assert!(rules.start(&one, &two, 6, 6).is_err());
+    /// assert!(Rules::default().start(&one, &two, 1, 8).is_err());
+    /// ```
+    pub fn start<'a>(
+        &self,
+        p_one: &'a dyn Player,
+        p_two: &'a dyn Player,
+        rows: usize,
+        cols: usize,
+    ) -> Result<Othello<'a>, String> {
+        if self.grid != Grid::Square && self.mask.is_some() {
+            return Err("Only square boards can have a shape.".to_string());
+        }
+        if self.grid != Grid::Square && self.topology != Topology::Flat {
+            return Err("Only square boards can wrap around.".to_string());
+        }
+        if self.grid != Grid::Square && self.layout != Layout::Diagonal {
+            return Err("Only square boards can start with a parallel layout.".to_string());
+        }
+        if self.grid == Grid::Hex && self.captures != Captures::All {
+            return Err(
+                "Hex boards only have one kind of line, so every line captures.".to_string(),
+            );
+        }
+        let (rows, cols) = self.grid.board_size(rows, cols)?;
+        let outline = self.grid.outline(rows, cols)?;
+
+        let mut board = Board::with_size(rows, cols);
+        if self.setup == Setup::Fixed {
+            let symbols = [p_one.get_symbol(), p_two.get_symbol()];
+            for (index, (row, col)) in self.grid.middle(rows, cols).into_iter().enumerate() {
+                board.set_cell(row, col, symbols[self.layout.owner(index)]);
+            }
+        }
+        let mut game = Othello::with_board(p_one, p_two, board, ActivePlayer::PlayerOne)
+            .with_variant(self.variant)
+            .with_topology(self.topology)
+            .with_setup(self.setup)
+            .with_grid(self.grid)
+            .with_captures(self.captures)
+            .with_swap_rule(self.swap);
+        for mask in outline.iter().chain(&self.mask) {
+            game = game.with_mask(mask.clone())?;
+        }
+        Ok(game)
+    }
+
+    /// # The rules a game is being played under
+    ///
+    /// Only what decides the moves and the winner from the current position on. The shape is already on the board
+    /// and the layout and swap rule only matter at the start, so they're left at their defaults.
+    ///
+    /// ```
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::rules::{Rules, Topology};
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let rules = Rules { topology: Topology::Torus, ..Rules::default() };
+    /// assert_eq!(rules, Rules::of_game(&rules.start(&one, &two, 4, 4).unwrap()));
+    /// ```
+    pub fn of_game(game: &Othello) -> Self {
+        Self {
+            variant: game.variant(),
+            topology: game.topology(),
+            setup: game.setup(),
+            grid: game.grid(),
+            captures: game.captures(),
+            ..Self::default()
+        }
+    }
+
+    /// # Picks up a game from a position
+    ///
+    /// The counterpart of `of_game`: `board` already has its shape, so `mask`, `layout` and `swap` aren't used.
+    pub fn resume<'a>(
+        &self,
+        p_one: &'a dyn Player,
+        p_two: &'a dyn Player,
+        board: Board,
+        active: ActivePlayer,
+    ) -> Othello<'a> {
+        Othello::with_board(p_one, p_two, board, active)
+            .with_variant(self.variant)
+            .with_topology(self.topology)
+            .with_setup(self.setup)
+            .with_grid(self.grid)
+            .with_captures(self.captures)
+    }
+}
+
+/// How a game is won.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Variant {
+    /// The player with more discs wins.
+    #[default]
+    Standard,
+    /// Anti-Othello: the player with fewer discs wins.
+    Misere,
+}
+
+impl Variant {
+    /// Turns a player's disc differential into their result, where positive is winning.
+    ///
+    /// ```
+    /// # use othlib::rules::Variant;
+    /// assert_eq!(4, Variant::Standard.outcome(4));
+    /// assert_eq!(-4, Variant::Misere.outcome(4));
+    /// ```
+    pub fn outcome(self, differential: isize) -> isize {
+        match self {
+            Variant::Standard => differential,
+            Variant::Misere => -differential,
+        }
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Variant::Standard),
+            "misere" | "anti" => Ok(Variant::Misere),
+            _ => Err(format!(
+                "`{}` is not a variant, the variants are `standard` and `misere`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Variant::Standard => write!(f, "standard"),
+            Variant::Misere => write!(f, "misere"),
+        }
+    }
+}
+
+/// How lines of discs run at the edges of the board.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Topology {
+    /// Lines end at the edges.
+    #[default]
+    Flat,
+    /// Lines carry on from the opposite edge, so every line goes all the way around. A line ends when it gets back to
+    /// the cell it started from.
+    Torus,
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(Topology::Flat),
+            "torus" | "wrap" => Ok(Topology::Torus),
+            _ => Err(format!(
+                "`{}` is not a topology, the topologies are `flat` and `torus`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Topology::Flat => write!(f, "flat"),
+            Topology::Torus => write!(f, "torus"),
+        }
+    }
+}
+
+/// How the four discs in the middle get there.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Setup {
+    /// The game starts with them already placed, diagonally.
+    #[default]
+    Fixed,
+    /// Reversi's original rule: the game starts with an empty board, and the players take turns placing discs on
+    /// the middle squares, without flipping anything, until they're all full.
+    Free,
+}
+
+impl FromStr for Setup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fixed" => Ok(Setup::Fixed),
+            "free" | "reversi" => Ok(Setup::Free),
+            _ => Err(format!(
+                "`{}` is not a setup, the setups are `fixed` and `free`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Setup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Setup::Fixed => write!(f, "fixed"),
+            Setup::Free => write!(f, "free"),
+        }
+    }
+}
+
+/// The steps of a square grid, the orthogonal ones first.
+const SQUARE_STEPS: [Step; 8] = [
+    Direction::N.step(),
+    Direction::E.step(),
+    Direction::S.step(),
+    Direction::W.step(),
+    Direction::NE.step(),
+    Direction::SE.step(),
+    Direction::SW.step(),
+    Direction::NW.step(),
+];
+
+/// The steps of a hex grid, counterclockwise from east.
+const HEX_STEPS: [Step; 6] = [
+    Step::new(0, 1),
+    Step::new(1, 0),
+    Step::new(1, -1),
+    Step::new(0, -1),
+    Step::new(-1, 0),
+    Step::new(-1, 1),
+];
+
+/// The steps of a cube: every way to move one or no cells along each axis, except staying put. The 6 orthogonal ones
+/// come first.
+const CUBE_STEPS: [Step; 26] = cube_steps();
+
+const fn cube_steps() -> [Step; 26] {
+    let mut steps = [Step::new(0, 0); 26];
+    let mut index = 0;
+    // One pass for the steps along one axis, then one for the rest
+    let mut orthogonal = true;
+    loop {
+        let mut offsets = 0;
+        while offsets < 27 {
+            let step = Step::layered(offsets / 9 - 1, offsets / 3 % 3 - 1, offsets % 3 - 1);
+            let axes =
+                (step.layers != 0) as usize + (step.rows != 0) as usize + (step.cols != 0) as usize;
+            if axes > 0 && (axes == 1) == orthogonal {
+                steps[index] = step;
+                index += 1;
+            }
+            offsets += 1;
+        }
+        if !orthogonal {
+            return steps;
+        }
+        orthogonal = false;
+    }
+}
+
+/// The kind of cells the board is made of.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Grid {
+    /// Square cells with eight neighbors.
+    #[default]
+    Square,
+    /// Hexagonal cells with six neighbors, on a board shaped like a hexagon. Cells are stored by axial coordinates:
+    /// the rows are the horizontal lines of hexes, and the columns run up and to the right. See `Mask::hexagon`.
+    Hex,
+    /// A cube of square cells with 26 neighbors, which lines run to through faces, edges and corners. The board
+    /// keeps the layers one above the other, bottom layer first, so a cube `size` cells across is a board of `size`
+    /// columns and `size * size` rows. See `Step::apply_in_cube`.
+    Cube,
+}
+
+impl Grid {
+    /// The steps lines of discs can run in. See `Captures::steps` for the ones that capture.
+    pub fn steps(self) -> &'static [Step] {
+        match self {
+            Grid::Square => &SQUARE_STEPS,
+            Grid::Hex => &HEX_STEPS,
+            Grid::Cube => &CUBE_STEPS,
+        }
+    }
+
+    /// # The rows and columns of the board for a grid `rows` by `cols` cells across
+    ///
+    /// They're the same for flat grids. Fails for boards less than 2 cells across, which have no room for the discs
+    /// the game starts with, and for cubes that aren't an even number of cells, at least 4, across.
+    ///
+    /// ```
+    /// # use othlib::rules::Grid;
+    /// assert_eq!(Ok((8, 6)), Grid::Square.board_size(8, 6));
+    /// assert!(Grid::Square.board_size(1, 6).is_err());
+    /// assert_eq!(Ok((16, 4)), Grid::Cube.board_size(4, 4));
+    /// assert!(Grid::Cube.board_size(4, 6).is_err());
+    /// ```
+    pub fn board_size(self, rows: usize, cols: usize) -> Result<(usize, usize), String> {
+        match self {
+            _ if rows < 2 || cols < 2 => Err(format!(
+                "A board needs at least 2 rows and 2 columns, not {}x{}.",
+                rows, cols
+            )),
+            Grid::Square | Grid::Hex => Ok((rows, cols)),
+            Grid::Cube if rows == cols && rows.is_multiple_of(2) && rows >= 4 => {
+                Ok((rows * rows, cols))
+            }
+            Grid::Cube => Err(format!(
+                "A cube needs the same even number of rows and columns, at least 4, not {}x{}.",
+                rows, cols
+            )),
+        }
+    }
+
+    /// # The shape the grid needs on a board of `rows` by `cols`, if any
+    ///
+    /// Fails for sizes the grid doesn't fit.
+    ///
+    /// ```
+    /// # use othlib::rules::Grid;
+    /// assert_eq!(None, Grid::Square.outline(8, 6).unwrap());
+    /// assert_eq!(37, Grid::Hex.outline(7, 7).unwrap().unwrap().open_cells());
+    /// assert!(Grid::Hex.outline(8, 8).is_err());
+    /// ```
+    pub fn outline(self, rows: usize, cols: usize) -> Result<Option<Mask>, String> {
+        match self {
+            Grid::Square | Grid::Cube => Ok(None),
+            Grid::Hex if rows == cols && !rows.is_multiple_of(2) && rows >= 5 => {
+                Ok(Some(Mask::hexagon(rows)))
+            }
+            Grid::Hex => Err(format!(
+                "A hex board needs the same odd number of rows and columns, at least 5, not {}x{}.",
+                rows, cols
+            )),
+        }
+    }
+
+    /// # The cells in the middle that games start with
+    ///
+    /// Listed so alternate cells go to each player: around the middle on flat grids, and checkered through the
+    /// middle 2x2x2 block of a cube. See `Layout::owner`.
+    ///
+    /// A square board starts with the 2x2 block in the middle. That block can't be centered on an odd side, so a
+    /// board with an odd number of both rows and columns starts with a cross around its empty center cell instead.
+    /// With one odd side, the block covers the middle line of that side and the line below it.
+    ///
+    /// ```
+    /// # use othlib::rules::Grid;
+    /// assert_eq!(vec![(1, 1), (1, 2), (2, 2), (2, 1)], Grid::Square.middle(4, 4));
+    /// assert_eq!(vec![(1, 2), (2, 3), (3, 2), (2, 1)], Grid::Square.middle(5, 5));
+    /// assert_eq!(vec![(1, 2), (1, 3), (2, 3), (2, 2)], Grid::Square.middle(5, 6));
+    /// assert_eq!(vec![(2, 3), (3, 2), (3, 1), (2, 1), (1, 2), (1, 3)], Grid::Hex.middle(5, 5));
+    /// // Layers 1 and 2 of a 4x4x4 cube start at rows 4 and 8
+    /// assert_eq!(
+    ///     vec![(5, 1), (5, 2), (6, 2), (6, 1), (9, 2), (10, 2), (10, 1), (9, 1)],
+    ///     Grid::Cube.middle(16, 4)
+    /// );
+    /// ```
+    pub fn middle(self, rows: usize, cols: usize) -> Vec<(usize, usize)> {
+        match self {
+            Grid::Square if !rows.is_multiple_of(2) && !cols.is_multiple_of(2) => vec![
+                (rows / 2 - 1, cols / 2),
+                (rows / 2, cols / 2 + 1),
+                (rows / 2 + 1, cols / 2),
+                (rows / 2, cols / 2 - 1),
+            ],
+            Grid::Square => vec![
+                (rows / 2 - 1, cols / 2 - 1),
+                (rows / 2 - 1, cols / 2),
+                (rows / 2, cols / 2),
+                (rows / 2, cols / 2 - 1),
+            ],
+            // The ring around the center, which starts empty
+            Grid::Hex => HEX_STEPS
+                .iter()
+                .filter_map(|step| step.apply(rows / 2, cols / 2))
+                .collect(),
+            // The square start in the lower layer, and again with the colors swapped in the upper one
+            Grid::Cube => {
+                let size = cols;
+                let square = Grid::Square.middle(size, size);
+                let lower = square
+                    .iter()
+                    .map(|&(row, col)| ((size / 2 - 1) * size + row, col));
+                let upper = square[1..]
+                    .iter()
+                    .chain(&square[..1])
+                    .map(|&(row, col)| (size / 2 * size + row, col));
+                lower.chain(upper).collect()
+            }
+        }
+    }
+}
+
+impl FromStr for Grid {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "square" => Ok(Grid::Square),
+            "hex" | "hexagonal" => Ok(Grid::Hex),
+            "cube" | "3d" => Ok(Grid::Cube),
+            _ => Err(format!(
+                "`{}` is not a grid, the grids are `square`, `hex` and `cube`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Grid::Square => write!(f, "square"),
+            Grid::Hex => write!(f, "hex"),
+            Grid::Cube => write!(f, "cube"),
+        }
+    }
+}
+
+/// Which lines of discs capture.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Captures {
+    /// Every line.
+    #[default]
+    All,
+    /// Lines along the rows and columns (and up and down through the layers of a cube), like a rook moves.
+    Orthogonal,
+    /// Lines that cross rows and columns at once, like a bishop moves.
+    Diagonal,
+}
+
+impl Captures {
+    /// # The steps of lines that capture on a grid
+    ///
+    /// Hex grids have only one kind of line, so every line captures there whatever the preset.
+    ///
+    /// ```
+    /// # use othlib::rules::{Captures, Grid};
+    /// # use othlib::{Direction, Step};
+    /// assert_eq!(4, Captures::Diagonal.steps(Grid::Square).len());
+    /// assert!(Captures::Orthogonal.steps(Grid::Square).contains(&Direction::N.step()));
+    /// assert_eq!(6, Captures::Orthogonal.steps(Grid::Cube).len());
+    /// assert!(Captures::Diagonal.steps(Grid::Cube).contains(&Step::layered(1, 1, 1)));
+    /// assert_eq!(6, Captures::Diagonal.steps(Grid::Hex).len());
+    /// ```
+    pub fn steps(self, grid: Grid) -> &'static [Step] {
+        let steps = grid.steps();
+        // Every grid lists its orthogonal steps first
+        let orthogonal = match grid {
+            Grid::Square => 4,
+            Grid::Cube => 6,
+            Grid::Hex => return steps,
+        };
+        match self {
+            Captures::All => steps,
+            Captures::Orthogonal => &steps[..orthogonal],
+            Captures::Diagonal => &steps[orthogonal..],
+        }
+    }
+}
+
+impl FromStr for Captures {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Captures::All),
+            "orthogonal" | "rook" => Ok(Captures::Orthogonal),
+            "diagonal" | "bishop" => Ok(Captures::Diagonal),
+            _ => Err(format!(
+                "`{}` is not a capture preset, the presets are `all`, `orthogonal` and `diagonal`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Captures {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Captures::All => write!(f, "all"),
+            Captures::Orthogonal => write!(f, "orthogonal"),
+            Captures::Diagonal => write!(f, "diagonal"),
+        }
+    }
+}
+
+/// How the discs the game starts with are arranged. Only square boards have a choice.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Layout {
+    /// Each player's discs are on a diagonal, crossing the other player's.
+    #[default]
+    Diagonal,
+    /// Each player's discs are side by side, on parallel lines.
+    Parallel,
+}
+
+impl Layout {
+    /// # Which player starts with a middle cell
+    ///
+    /// Player one is 0 and player two 1. `index` is the cell's place in `Grid::middle`.
+    ///
+    /// ```
+    /// # use othlib::player::minimax::MinimaxPlayer;
+    /// # use othlib::rules::{Layout, Rules};
+    /// let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+    /// let rules = Rules { layout: Layout::Parallel, ..Rules::default() };
+    /// let game = rules.start(&one, &two, 4, 4).unwrap();
+    /// assert_eq!("..../.XX./.OO./....", game.board().encode());
+    /// assert_eq!(vec![(3, 0), (3, 1), (3, 2), (3, 3)], game.successors('X'));
+    /// ```
+    pub fn owner(self, index: usize) -> usize {
+        match self {
+            Layout::Diagonal => index % 2,
+            Layout::Parallel => index / 2 % 2,
+        }
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "diagonal" | "cross" => Ok(Layout::Diagonal),
+            "parallel" | "straight" => Ok(Layout::Parallel),
+            _ => Err(format!(
+                "`{}` is not a layout, the layouts are `diagonal` and `parallel`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Layout::Diagonal => write!(f, "diagonal"),
+            Layout::Parallel => write!(f, "parallel"),
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Generates labeled training data by letting players play each other.
+//!
+//! Each game starts with a number of uniformly random moves (or an opening from a set) so bots don't replay the
+//! same game, then the configured players take over. Every position where a player had to choose a move is written out as a CSV
+//! row that `eval::train` can read:
+//!
+//! ```text
+//! game,ply,board,side,moves,result,score
+//! 0,0,..../.XO./.OX./....,X,..1./...1/1.../.1..,-4,
+//! ```
+//!
+//! `board` is `Board::encode`d, `moves` marks the legal moves with `1` in the same layout, `result` is the
+//! final disc differential for `side`, and `score` is the exact solver score when the position was close enough
+//! to the end to solve (and empty otherwise).
+
+use crate::eval::disc_differential;
+use crate::openings::Opening;
+use crate::player::minimax::MinimaxPlayer;
+use crate::player::spec::PlayerSpec;
+use crate::rng::Rng;
+use crate::rules::Rules;
+use crate::Othello;
+
+use std::io::{self, Write};
+use std::sync::mpsc;
+use std::thread;
+
+pub const CSV_HEADER: &str = "game,ply,board,side,moves,result,score";
+
+/// A position from a finished self-play game.
+#[derive(Clone, Debug)]
+pub struct Position {
+    pub game: usize,
+    pub ply: usize,
+    pub board: String,
+    pub side: char,
+    pub moves: String,
+    pub result: isize,
+    pub score: Option<isize>,
+}
+
+impl Position {
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.game,
+            self.ply,
+            self.board,
+            self.side,
+            self.moves,
+            self.result,
+            self.score.map(|x| x.to_string()).unwrap_or_default()
+        )
+    }
+}
+
+/// # Self-play configuration
+///
+/// Player one plays as `X` and player two as `O`.
+#[derive(Clone, Debug)]
+pub struct SelfPlay {
+    pub players: (PlayerSpec, PlayerSpec),
+    pub games: usize,
+    pub rows: usize,
+    pub cols: usize,
+    /// How many moves at the start of each game are picked at random.
+    pub random_moves: usize,
+    /// When there are any, game `index` starts with opening `index % openings.len()` instead of random moves.
+    pub openings: Vec<Opening>,
+    /// Positions with at most this many empty cells get an exact solver score.
+    pub solve_empties: Option<usize>,
+    pub rules: Rules,
+    pub threads: usize,
+    pub seed: u64,
+}
+
+impl SelfPlay {
+    pub fn new(players: (PlayerSpec, PlayerSpec), games: usize, rows: usize, cols: usize) -> Self {
+        Self {
+            players,
+            games,
+            rows,
+            cols,
+            random_moves: 4,
+            openings: vec![],
+            solve_empties: None,
+            rules: Rules::default(),
+            threads: thread::available_parallelism().map_or(1, |x| x.get()),
+            seed: 0,
+        }
+    }
+
+    /// Plays game number `index` and returns its positions.
+    pub fn play_game(&self, index: usize) -> Result<Vec<Position>, String> {
+        let player_one = self.players.0.build('X')?;
+        let player_two = self.players.1.build('O')?;
+        let mut game = self
+            .rules
+            .start(&*player_one, &*player_two, self.rows, self.cols)?;
+        let mut rng = Rng::with_seed(self.seed.wrapping_add(index as u64));
+        let mut positions = vec![];
+        let mut opening = self
+            .openings
+            .get(index % self.openings.len().max(1))
+            .map_or(&[][..], Vec::as_slice)
+            .iter();
+
+        while game.has_more_moves() {
+            let symbol = game.get_active_symbol();
+            let successors = game.successors(symbol);
+            if successors.is_empty() {
+                game.change_active_player();
+                continue;
+            }
+
+            let ply = positions.len();
+            let board = game.board();
+            let empties = board.empties();
+            positions.push(Position {
+                game: index,
+                ply,
+                board: board.encode(),
+                side: symbol,
+                moves: move_mask(&game, &successors),
+                result: 0,
+                score: match self.solve_empties {
+                    // The solver scores the variant's outcome, which is labelled as a disc differential like `result`
+                    Some(limit) if empties <= limit => Some(
+                        self.rules
+                            .variant
+                            .outcome(MinimaxPlayer::new(symbol).search(&game).score),
+                    ),
+                    _ => None,
+                },
+            });
+
+            let opening_move = if self.openings.is_empty() {
+                (ply < self.random_moves).then(|| successors[rng.below(successors.len())])
+            } else {
+                opening.next().copied()
+            };
+            match opening_move {
+                Some((row, col)) => game
+                    .try_move(row, col)
+                    .map_err(|err| format!("The opening doesn't fit this board: {}", err))?,
+                None => {
+                    game.play_turn();
+                }
+            }
+        }
+
+        let result = disc_differential(&game, 'X');
+        for position in &mut positions {
+            position.result = if position.side == 'X' {
+                result
+            } else {
+                -result
+            };
+        }
+
+        Ok(positions)
+    }
+
+    /// Plays every game across `threads` worker threads and writes the positions to `out` as CSV.
+    ///
+    /// Games finish in whatever order the workers get to them, so rows are grouped by game but the games aren't
+    /// sorted. Returns the number of positions written.
+    pub fn run<W: Write>(&self, mut out: W) -> io::Result<usize> {
+        // Fail before spawning anything if a player can't be built (e.g. a missing weight file)
+        self.players
+            .0
+            .build('X')
+            .and(self.players.1.build('O'))
+            .map_err(io::Error::other)?;
+
+        writeln!(out, "{}", CSV_HEADER)?;
+        let threads = self.threads.clamp(1, self.games.max(1));
+        let (sender, receiver) = mpsc::channel();
+        let mut written = 0;
+
+        thread::scope(|scope| {
+            for worker in 0..threads {
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    for index in (worker..self.games).step_by(threads) {
+                        if sender.send(self.play_game(index)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            for positions in receiver {
+                let positions = positions.map_err(io::Error::other)?;
+                for position in &positions {
+                    writeln!(out, "{}", position.to_csv())?;
+                }
+                written += positions.len();
+            }
+            out.flush()
+        })?;
+
+        Ok(written)
+    }
+}
+
+/// Marks `moves` with `1` on an otherwise empty grid laid out like `Board::encode`.
+fn move_mask(game: &Othello, moves: &[(usize, usize)]) -> String {
+    let board = game.board();
+    (0..board.rows())
+        .map(|row| {
+            (0..board.cols())
+                .map(|col| {
+                    if moves.contains(&(row, col)) {
+                        '1'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::train::parse_samples;
+    use crate::player::spec::PlayerKind;
+
+    #[test]
+    fn writes_trainable_positions() {
+        let spec = PlayerSpec {
+            depth: Some(1),
+            ..PlayerSpec::new(PlayerKind::Minimax)
+        };
+        let mut config = SelfPlay::new((spec.clone(), spec), 3, 4, 4);
+        config.solve_empties = Some(6);
+        config.threads = 2;
+
+        let mut out = vec![];
+        let written = config.run(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(written + 1, text.lines().count());
+
+        let results = parse_samples(&text, "result").unwrap();
+        let scores = parse_samples(&text, "score").unwrap();
+        assert_eq!(written, results.len());
+        assert!(!scores.is_empty() && scores.len() < written);
+    }
+
+    #[test]
+    fn games_start_from_openings() {
+        let spec = PlayerSpec {
+            depth: Some(1),
+            ..PlayerSpec::new(PlayerKind::Minimax)
+        };
+        let mut config = SelfPlay::new((spec.clone(), spec), 2, 4, 4);
+        config.openings = vec![vec![(0, 2), (0, 1)], vec![(1, 3)]];
+        let positions = config.play_game(0).unwrap();
+        assert_eq!(".OX./.OX./.OX./....", positions[2].board);
+        assert_eq!('X', positions[2].side);
+        let positions = config.play_game(1).unwrap();
+        assert_eq!("..../.XXX/.OX./....", positions[1].board);
+        assert_eq!('O', positions[1].side);
+
+        config.openings = vec![vec![(0, 0)]];
+        assert!(config.play_game(0).is_err());
+    }
+
+    #[test]
+    fn games_are_reproducible() {
+        let spec = PlayerSpec {
+            depth: Some(1),
+            ..PlayerSpec::new(PlayerKind::Minimax)
+        };
+        let mut config = SelfPlay::new((spec.clone(), spec), 1, 4, 4);
+        config.random_moves = 10;
+        let first = config.play_game(5).unwrap();
+        let second = config.play_game(5).unwrap();
+        assert_eq!(
+            first.iter().map(Position::to_csv).collect::<Vec<_>>(),
+            second.iter().map(Position::to_csv).collect::<Vec<_>>()
+        );
+    }
+}
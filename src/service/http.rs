@@ -0,0 +1,146 @@
+//! Just enough HTTP/1.1 for the game service: one request per connection, JSON bodies both ways.
+
+use super::json::Value;
+
+use std::io::{self, BufRead, Read, Write};
+
+/// Request bodies larger than this are refused.
+pub const MAX_BODY: usize = 64 * 1024;
+
+/// Header lines longer than this are refused.
+const MAX_LINE: usize = 8 * 1024;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub method: String,
+    /// The path without the query string.
+    pub path: String,
+    pub body: String,
+}
+
+impl Request {
+    pub fn new(method: &str, path: &str, body: &str) -> Self {
+        Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    /// Parses the body as JSON. An empty body is an empty object.
+    pub fn json(&self) -> Result<Value, String> {
+        if self.body.trim().is_empty() {
+            Ok(Value::Object(vec![]))
+        } else {
+            Value::parse(&self.body).map_err(|err| format!("The body isn't valid JSON: {}", err))
+        }
+    }
+
+    /// # Reads a request from a client
+    ///
+    /// Returns `None` if the client closed the connection without sending anything.
+    ///
+    /// ```
+    /// # use othlib::service::http::Request;
+    /// let text = "POST /games?x=1 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 11\r\n\r\n{\"rows\":6}\n";
+    /// let request = Request::read(&mut text.as_bytes()).unwrap().unwrap();
+    /// assert_eq!(Request::new("POST", "/games", "{\"rows\":6}\n"), request);
+    /// ```
+    pub fn read<R: BufRead>(reader: &mut R) -> io::Result<Option<Self>> {
+        let request_line = match read_line(reader)? {
+            Some(line) => line,
+            None => return Ok(None),
+        };
+        let mut words = request_line.split_whitespace();
+        let (method, target) = match (words.next(), words.next()) {
+            (Some(method), Some(target)) => (method.to_string(), target),
+            _ => return Err(invalid("Malformed request line")),
+        };
+        let path = target.split('?').next().unwrap_or("").to_string();
+
+        let mut length = 0;
+        loop {
+            let line = read_line(reader)?.ok_or_else(|| invalid("The headers never ended"))?;
+            if line.is_empty() {
+                break;
+            }
+            let mut pair = line.splitn(2, ':');
+            let (name, value) = (pair.next().unwrap_or(""), pair.next().unwrap_or("").trim());
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value
+                    .parse::<usize>()
+                    .map_err(|_| invalid("Invalid Content-Length"))?;
+            }
+        }
+        if length > MAX_BODY {
+            return Err(invalid("The body is too large"));
+        }
+
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        let body = String::from_utf8(body).map_err(|_| invalid("The body isn't UTF-8"))?;
+        Ok(Some(Self { method, path, body }))
+    }
+}
+
+/// Reads a line without its line ending. Returns `None` at the end of the input.
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    let read = reader.by_ref().take(MAX_LINE as u64).read_line(&mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with('\n') {
+        return Err(invalid("The line is too long"));
+    }
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    pub fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    /// An error response with a body like `{"error": "..."}`.
+    pub fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: Value::object(vec![("error", message.into())]),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            _ => "Internal Server Error",
+        }
+    }
+
+    pub fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let body = format!("{}\n", self.body);
+        write!(
+            out,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.reason(),
+            body.len(),
+            body
+        )?;
+        out.flush()
+    }
+}
@@ -0,0 +1,353 @@
+//! Just enough JSON for the game service's requests and responses.
+
+use std::fmt::{self, Write};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Keys stay in the order they were written in.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Builds an object from key and value pairs.
+    pub fn object<'a, I>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, Value)>,
+    {
+        Value::Object(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// Looks up a key of an object. Anything else has no keys.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(pairs) => pairs.iter().find(|(name, _)| name == key).map(|(_, x)| x),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The value as a whole, non-negative number.
+    pub fn as_usize(&self) -> Option<usize> {
+        match *self {
+            Value::Number(x) if x >= 0.0 && x.fract() == 0.0 && x <= usize::MAX as f64 => {
+                Some(x as usize)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// # Parses a JSON document
+    ///
+    /// ```
+    /// # use othlib::service::json::Value;
+    /// let value = Value::parse(r#"{"rows": 6, "players": ["human", "minimax:3"], "go": true}"#).unwrap();
+    /// assert_eq!(Some(6), value.get("rows").and_then(Value::as_usize));
+    /// assert_eq!(Some("minimax:3"), value.get("players").and_then(|x| x.as_array()?[1].as_str()));
+    /// assert_eq!(Some(&Value::Bool(true)), value.get("go"));
+    /// assert!(Value::parse("{\"rows\": }").is_err());
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            index: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.index < parser.chars.len() {
+            return Err(parser.error("Unexpected text after the end"));
+        }
+        Ok(value)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(x: usize) -> Self {
+        Value::Number(x as f64)
+    }
+}
+
+impl From<isize> for Value {
+    fn from(x: isize) -> Self {
+        Value::Number(x as f64)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(x: bool) -> Self {
+        Value::Bool(x)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(x: &str) -> Self {
+        Value::String(x.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(x: String) -> Self {
+        Value::String(x)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(x: Option<T>) -> Self {
+        x.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(x: Vec<T>) -> Self {
+        Value::Array(x.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Writes the value as compact JSON.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(x) => write!(f, "{}", x),
+            Value::Number(x) if x.is_finite() => write!(f, "{}", x),
+            Value::Number(_) => write!(f, "null"),
+            Value::String(text) => write_string(f, text),
+            Value::Array(values) => {
+                f.write_char('[')?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_char(']')
+            }
+            Value::Object(pairs) => {
+                f.write_char('{')?;
+                for (index, (key, value)) in pairs.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, text: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in text.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// How deeply arrays and objects can nest, so hostile input can't overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> String {
+        format!("{} at character {}", message, self.index)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.index += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.index += 1;
+        c
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(&format!("Expected `{}`", expected))),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        for expected in word.chars() {
+            if self.next() != Some(expected) {
+                return Err(self.error(&format!("Expected `{}`", word)));
+            }
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('n') => self.literal("null", Value::Null),
+            Some('t') => self.literal("true", Value::Bool(true)),
+            Some('f') => self.literal("false", Value::Bool(false)),
+            Some('"') => self.string().map(Value::String),
+            Some('[') | Some('{') if self.depth >= MAX_DEPTH => {
+                Err(self.error("Nested too deeply"))
+            }
+            Some('[') => {
+                self.depth += 1;
+                let value = self.array();
+                self.depth -= 1;
+                value
+            }
+            Some('{') => {
+                self.depth += 1;
+                let value = self.object();
+                self.depth -= 1;
+                value
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.error("Expected a value")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.index;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+        {
+            self.index += 1;
+        }
+        self.chars[start..self.index]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .map(Value::Number)
+            .map_err(|_| self.error("Invalid number"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(text),
+                Some('\\') => match self.next() {
+                    Some('n') => text.push('\n'),
+                    Some('r') => text.push('\r'),
+                    Some('t') => text.push('\t'),
+                    Some('b') => text.push('\u{8}'),
+                    Some('f') => text.push('\u{c}'),
+                    Some('u') => {
+                        let code = (0..4).filter_map(|_| self.next()).collect::<String>();
+                        let c = u32::from_str_radix(&code, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error("Invalid unicode escape"))?;
+                        text.push(c);
+                    }
+                    Some(c) => text.push(c),
+                    None => return Err(self.error("Unterminated string")),
+                },
+                Some(c) => text.push(c),
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        let mut values = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.index += 1;
+            return Ok(Value::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(values)),
+                _ => return Err(self.error("Expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        let mut pairs = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.index += 1;
+            return Ok(Value::Object(pairs));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            pairs.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some('}') => return Ok(Value::Object(pairs)),
+                _ => return Err(self.error("Expected `,` or `}`")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let text = r#"{"a":[1,-2.5,null,true],"b":"quote \" slash \\ line \n","c":{}}"#;
+        let value = Value::parse(text).unwrap();
+        assert_eq!(text, value.to_string());
+        assert_eq!(value, Value::parse(&value.to_string()).unwrap());
+        let escaped = Value::parse(r#""\u00e9""#).unwrap();
+        assert_eq!(Some("é"), escaped.as_str());
+    }
+}
@@ -0,0 +1,527 @@
+//! An HTTP service that hosts games and answers with JSON.
+//!
+//! | Request | Body | Answer |
+//! |---|---|---|
+//! | `POST /games` | `{"rows": 8, "cols": 8, "players": ["human", "minimax:3"]}` | the new game |
+//! | `GET /games/<id>` | | the game |
+//! | `GET /games/<id>/moves` | | `{"moves": [{"row": 2, "col": 3}, ...]}`, the legal moves |
+//! | `POST /games/<id>/moves` | `{"row": 2, "col": 3}` | the game after the move |
+//! | `POST /best-move` | `{"board": "..../.XO./.OX./....", "side": "X", "player": "minimax:5"}` | `{"move": {"row": 0, "col": 2}}` |
+//!
+//! Every field of `POST /games` is optional, games are 8x8 between two humans unless told otherwise. Player one plays
+//! `X` and moves first. Moves are only accepted for human players: after each one, bots move (and players without
+//! moves pass) until it's a human's turn again or the game is over. Moves go through `Othello::try_move`, so an illegal
+//! move is answered with `409 Conflict` and leaves the game untouched.
+//!
+//! `/best-move` doesn't create a game. Without `player` it asks the engine the service was started with. A `null`
+//! move means the side has to pass.
+//!
+//! Errors are answered with `{"error": "..."}`. Games are kept in memory until the service stops.
+
+pub mod http;
+pub mod json;
+
+use self::http::{Request, Response};
+use self::json::Value;
+use crate::board::Board;
+use crate::player::spec::{PlayerKind, PlayerSpec};
+use crate::protocol::choose_move;
+use crate::rules::Rules;
+use crate::{ActivePlayer, Othello};
+
+use std::collections::BTreeMap;
+use std::io::{self, BufReader};
+use std::net::{TcpListener, TcpStream};
+
+/// The largest board the service creates or searches.
+pub const MAX_SIZE: usize = 16;
+
+const SYMBOLS: (char, char) = ('X', 'O');
+
+/// A game kept by the service. Players are kept as specs and built whenever they're needed.
+struct GameRecord {
+    players: (PlayerSpec, PlayerSpec),
+    board: Board,
+    active: ActivePlayer,
+    moves: Vec<Option<(usize, usize)>>,
+}
+
+impl GameRecord {
+    /// Runs `action` on the game as an `Othello`, keeping whatever it does to the board.
+    fn with_game<T, F>(&mut self, action: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut Othello, &mut Vec<Option<(usize, usize)>>) -> Result<T, String>,
+    {
+        let one = self.players.0.build(SYMBOLS.0)?;
+        let two = self.players.1.build(SYMBOLS.1)?;
+        let mut game = Othello::with_board(&*one, &*two, self.board.clone(), self.active);
+        let result = action(&mut game, &mut self.moves);
+        self.board = game.board().clone();
+        self.active = game.active_player();
+        result
+    }
+
+    fn spec(&self, player: ActivePlayer) -> &PlayerSpec {
+        match player {
+            ActivePlayer::PlayerOne => &self.players.0,
+            ActivePlayer::PlayerTwo => &self.players.1,
+        }
+    }
+
+    /// Lets bots move and players without moves pass, until it's a human's turn or the game is over.
+    fn advance(&mut self) -> Result<(), String> {
+        let bots = (self.players.0.kind.is_bot(), self.players.1.kind.is_bot());
+        self.with_game(|game, moves| {
+            while game.has_more_moves() {
+                let active = game.active_player();
+                if !game.player_has_more_moves(active) {
+                    game.change_active_player();
+                    moves.push(None);
+                    continue;
+                }
+                let is_bot = match active {
+                    ActivePlayer::PlayerOne => bots.0,
+                    ActivePlayer::PlayerTwo => bots.1,
+                };
+                if !is_bot {
+                    break;
+                }
+                let (row, col) = game.get_move();
+                game.try_move(row, col)?;
+                moves.push(Some((row, col)));
+            }
+            Ok(())
+        })
+    }
+
+    fn state(&mut self, id: u64) -> Result<Value, String> {
+        let players = vec![self.players.0.to_string(), self.players.1.to_string()];
+        let (rows, cols) = (self.board.rows(), self.board.cols());
+        let moves = self
+            .moves
+            .iter()
+            .map(|x| x.map(move_json))
+            .collect::<Vec<_>>();
+        self.with_game(|game, _| {
+            let over = !game.has_more_moves();
+            let counts = game.board().char_counts();
+            let score = |symbol: char| Value::from(*counts.get(&symbol).unwrap_or(&0));
+            Ok(Value::object(vec![
+                ("id", Value::Number(id as f64)),
+                ("rows", rows.into()),
+                ("cols", cols.into()),
+                ("board", game.board().encode().into()),
+                ("players", players.into()),
+                (
+                    "next",
+                    (!over).then(|| game.get_active_symbol().to_string()).into(),
+                ),
+                (
+                    "score",
+                    Value::object(vec![("X", score(SYMBOLS.0)), ("O", score(SYMBOLS.1))]),
+                ),
+                ("moves", moves.into()),
+                ("over", over.into()),
+                ("winner", over.then(|| game.get_winner_number()).into()),
+                ("result", game.get_winner().into()),
+            ]))
+        })
+    }
+}
+
+fn move_json((row, col): (usize, usize)) -> Value {
+    Value::object(vec![("row", row.into()), ("col", col.into())])
+}
+
+/// # Games hosted over HTTP
+///
+/// `handle` answers a single request, `serve` listens for them.
+pub struct GameService {
+    games: BTreeMap<u64, GameRecord>,
+    next_id: u64,
+    engine: PlayerSpec,
+}
+
+impl GameService {
+    /// Creates a service without games. `engine` answers `/best-move` requests that don't name a player.
+    pub fn new(engine: PlayerSpec) -> Self {
+        Self {
+            games: BTreeMap::new(),
+            next_id: 1,
+            engine,
+        }
+    }
+
+    /// Answers a request.
+    pub fn handle(&mut self, request: &Request) -> Response {
+        let segments = request
+            .path
+            .split('/')
+            .filter(|x| !x.is_empty())
+            .collect::<Vec<_>>();
+        let method = request.method.as_str();
+
+        let result = match (method, segments.as_slice()) {
+            ("POST", ["games"]) => self.create(request),
+            ("GET", ["games", id]) => self
+                .game(id)
+                .and_then(|(id, game)| game.state(id).map_err(Error::Internal)),
+            ("GET", ["games", id, "moves"]) => self.legal_moves(id),
+            ("POST", ["games", id, "moves"]) => self.play(id, request),
+            ("POST", ["best-move"]) => self.best_move(request),
+            (_, ["games"])
+            | (_, ["games", _])
+            | (_, ["games", _, "moves"])
+            | (_, ["best-move"]) => {
+                return Response::error(405, &format!("{} isn't allowed here", method))
+            }
+            _ => return Response::error(404, "There's nothing here"),
+        };
+
+        match result {
+            Ok(body) if method == "POST" && segments == ["games"] => Response { status: 201, body },
+            Ok(body) => Response::ok(body),
+            Err(err) => err.into_response(),
+        }
+    }
+
+    fn game(&mut self, id: &str) -> Result<(u64, &mut GameRecord), Error> {
+        let id = id
+            .parse::<u64>()
+            .map_err(|_| Error::NotFound(format!("`{}` isn't a game id", id)))?;
+        self.games
+            .get_mut(&id)
+            .map(|game| (id, game))
+            .ok_or_else(|| Error::NotFound(format!("There is no game {}", id)))
+    }
+
+    fn create(&mut self, request: &Request) -> Result<Value, Error> {
+        let body = request.json().map_err(Error::BadRequest)?;
+        let size = |key: &str| match body.get(key) {
+            None => Ok(8),
+            Some(value) => value
+                .as_usize()
+                .filter(|x| (4..=MAX_SIZE).contains(x) && x.is_multiple_of(2))
+                .ok_or_else(|| {
+                    Error::BadRequest(format!(
+                        "`{}` must be an even number from 4 to {}",
+                        key, MAX_SIZE
+                    ))
+                }),
+        };
+        let (rows, cols) = (size("rows")?, size("cols")?);
+
+        let specs = match body.get("players") {
+            None => vec![PlayerSpec::new(PlayerKind::Human); 2],
+            Some(players) => players
+                .as_array()
+                .filter(|players| players.len() == 2)
+                .ok_or_else(|| Error::BadRequest("`players` must list two players".to_string()))?
+                .iter()
+                .map(parse_spec)
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+        let players = (specs[0].clone(), specs[1].clone());
+
+        // Build the players once so a bad spec (like a missing weight file) fails now instead of mid-game
+        let one = players.0.build(SYMBOLS.0).map_err(Error::BadRequest)?;
+        let two = players.1.build(SYMBOLS.1).map_err(Error::BadRequest)?;
+        let board = Othello::with_players(&*one, &*two, rows, cols)
+            .board()
+            .clone();
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut game = GameRecord {
+            players,
+            board,
+            active: ActivePlayer::PlayerOne,
+            moves: vec![],
+        };
+        game.advance().map_err(Error::Internal)?;
+        let state = game.state(id).map_err(Error::Internal)?;
+        self.games.insert(id, game);
+        Ok(state)
+    }
+
+    fn legal_moves(&mut self, id: &str) -> Result<Value, Error> {
+        let (_, game) = self.game(id)?;
+        let moves = game
+            .with_game(|game, _| {
+                Ok(if game.has_more_moves() {
+                    game.successors(game.get_active_symbol())
+                } else {
+                    vec![]
+                })
+            })
+            .map_err(Error::Internal)?;
+        Ok(Value::object(vec![(
+            "moves",
+            moves.into_iter().map(move_json).collect::<Vec<_>>().into(),
+        )]))
+    }
+
+    fn play(&mut self, id: &str, request: &Request) -> Result<Value, Error> {
+        let body = request.json().map_err(Error::BadRequest)?;
+        let coords = body
+            .get("row")
+            .and_then(Value::as_usize)
+            .zip(body.get("col").and_then(Value::as_usize))
+            .ok_or_else(|| Error::BadRequest("A move needs a `row` and a `col`".to_string()))?;
+
+        let (id, game) = self.game(id)?;
+        if game.spec(game.active).kind.is_bot() {
+            return Err(Error::Conflict("It's not a human's turn".to_string()));
+        }
+        game.with_game(|game, moves| {
+            game.try_move(coords.0, coords.1)?;
+            moves.push(Some(coords));
+            Ok(())
+        })
+        .map_err(Error::Conflict)?;
+        game.advance().map_err(Error::Internal)?;
+        game.state(id).map_err(Error::Internal)
+    }
+
+    fn best_move(&mut self, request: &Request) -> Result<Value, Error> {
+        let body = request.json().map_err(Error::BadRequest)?;
+        let board = body
+            .get("board")
+            .and_then(Value::as_str)
+            .and_then(Board::decode)
+            .filter(|board| board.rows() <= MAX_SIZE && board.cols() <= MAX_SIZE)
+            .ok_or_else(|| {
+                Error::BadRequest(format!(
+                    "`board` must be an encoded board no larger than {}x{}",
+                    MAX_SIZE, MAX_SIZE
+                ))
+            })?;
+        let symbols = [SYMBOLS.0, SYMBOLS.1];
+        if board
+            .char_counts()
+            .keys()
+            .any(|symbol| !symbols.contains(symbol))
+        {
+            return Err(Error::BadRequest(
+                "`board` may only contain `X`, `O` and `.`".to_string(),
+            ));
+        }
+        let (side, opponent) = match body.get("side").and_then(Value::as_str) {
+            Some("X") => SYMBOLS,
+            Some("O") => (SYMBOLS.1, SYMBOLS.0),
+            _ => return Err(Error::BadRequest("`side` must be `X` or `O`".to_string())),
+        };
+        let spec = match body.get("player") {
+            Some(player) => parse_spec(player)?,
+            None => self.engine.clone(),
+        };
+        if !spec.kind.is_bot() {
+            return Err(Error::BadRequest("`player` must be a bot".to_string()));
+        }
+
+        let chosen = choose_move(&spec, board, side, opponent, &Rules::default())
+            .map_err(Error::BadRequest)?;
+        Ok(Value::object(vec![("move", chosen.map(move_json).into())]))
+    }
+
+    /// Reads one request from `stream` and answers it.
+    pub fn handle_connection(&mut self, stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let response = match Request::read(&mut reader) {
+            Ok(Some(request)) => self.handle(&request),
+            Ok(None) => return Ok(()),
+            Err(err) => Response::error(400, &err.to_string()),
+        };
+        response.write_to(&mut &stream)
+    }
+
+    /// Answers requests on `listener` one at a time, forever.
+    pub fn serve(&mut self, listener: &TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            if let Err(err) = stream.and_then(|stream| self.handle_connection(stream)) {
+                eprintln!("Couldn't answer a request: {}", err);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a player spec from a request. Specs that would start programs on the server aren't allowed.
+fn parse_spec(value: &Value) -> Result<PlayerSpec, Error> {
+    let spec = value
+        .as_str()
+        .ok_or_else(|| Error::BadRequest("Players must be strings like `minimax:3`".to_string()))?
+        .parse::<PlayerSpec>()
+        .map_err(Error::BadRequest)?;
+    if spec.kind == PlayerKind::External {
+        return Err(Error::BadRequest(
+            "External players can't be started through the service".to_string(),
+        ));
+    }
+    Ok(spec)
+}
+
+/// A request that couldn't be answered, and the status that says why.
+enum Error {
+    BadRequest(String),
+    NotFound(String),
+    Conflict(String),
+    Internal(String),
+}
+
+impl Error {
+    fn into_response(self) -> Response {
+        match self {
+            Error::BadRequest(message) => Response::error(400, &message),
+            Error::NotFound(message) => Response::error(404, &message),
+            Error::Conflict(message) => Response::error(409, &message),
+            Error::Internal(message) => Response::error(500, &message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::thread;
+
+    fn service() -> GameService {
+        GameService::new("minimax:depth=2".parse().unwrap())
+    }
+
+    fn post(service: &mut GameService, path: &str, body: &str) -> Response {
+        service.handle(&Request::new("POST", path, body))
+    }
+
+    fn get(service: &mut GameService, path: &str) -> Response {
+        service.handle(&Request::new("GET", path, ""))
+    }
+
+    #[test]
+    fn plays_against_a_bot() {
+        let mut service = service();
+        let created = post(
+            &mut service,
+            "/games",
+            r#"{"rows": 4, "cols": 4, "players": ["human", "minimax:depth=1"]}"#,
+        );
+        assert_eq!(201, created.status);
+        assert_eq!(Some("X"), created.body.get("next").and_then(Value::as_str));
+        let board = created.body.get("board").cloned();
+
+        let legal = get(&mut service, "/games/1/moves");
+        assert_eq!(
+            4,
+            legal
+                .body
+                .get("moves")
+                .and_then(Value::as_array)
+                .unwrap()
+                .len()
+        );
+
+        let illegal = post(&mut service, "/games/1/moves", r#"{"row": 0, "col": 0}"#);
+        assert_eq!(409, illegal.status);
+        assert_eq!(
+            board,
+            get(&mut service, "/games/1").body.get("board").cloned()
+        );
+
+        let played = post(&mut service, "/games/1/moves", r#"{"row": 0, "col": 2}"#);
+        assert_eq!(200, played.status);
+        // The bot answered straight away
+        let moves = played.body.get("moves").and_then(Value::as_array).unwrap();
+        assert_eq!(2, moves.len());
+        assert_eq!(Some("X"), played.body.get("next").and_then(Value::as_str));
+    }
+
+    #[test]
+    fn bot_games_finish_when_created() {
+        let mut service = service();
+        let created = post(
+            &mut service,
+            "/games",
+            r#"{"rows": 4, "cols": 4, "players": ["minimax:depth=1", "minimax:depth=1"]}"#,
+        );
+        assert_eq!(Some(&Value::Bool(true)), created.body.get("over"));
+        assert!(created.body.get("result").and_then(Value::as_str).is_some());
+        let played = post(&mut service, "/games/1/moves", r#"{"row": 0, "col": 0}"#);
+        assert_eq!(409, played.status);
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        let mut service = service();
+        assert_eq!(400, post(&mut service, "/games", "{").status);
+        assert_eq!(400, post(&mut service, "/games", r#"{"rows": 5}"#).status);
+        assert_eq!(
+            400,
+            post(
+                &mut service,
+                "/games",
+                r#"{"players": ["external:cmd=ls", "human"]}"#
+            )
+            .status
+        );
+        assert_eq!(404, get(&mut service, "/games/7").status);
+        assert_eq!(404, get(&mut service, "/nothing").status);
+        assert_eq!(405, get(&mut service, "/games").status);
+        assert_eq!(201, post(&mut service, "/games", "").status);
+        assert_eq!(
+            400,
+            post(&mut service, "/games/1/moves", r#"{"row": 2}"#).status
+        );
+    }
+
+    #[test]
+    fn best_move_is_stateless() {
+        let mut service = service();
+        let body = r#"{"board": "..../.XO./.OX./....", "side": "O"}"#;
+        let answer = post(&mut service, "/best-move", body);
+        let chosen = answer.body.get("move").unwrap();
+        let coords = (
+            chosen.get("row").and_then(Value::as_usize).unwrap(),
+            chosen.get("col").and_then(Value::as_usize).unwrap(),
+        );
+        assert!([(0, 1), (1, 0), (2, 3), (3, 2)].contains(&coords));
+
+        let pass = r#"{"board": "XXXX/XXXX/XXXX/XXX.", "side": "O", "player": "minimax:1"}"#;
+        assert_eq!(
+            Some(&Value::Null),
+            post(&mut service, "/best-move", pass).body.get("move")
+        );
+        let human = r#"{"board": "..../.XO./.OX./....", "side": "O", "player": "human"}"#;
+        assert_eq!(400, post(&mut service, "/best-move", human).status);
+        assert!(service.games.is_empty());
+    }
+
+    #[test]
+    fn answers_over_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            let body = r#"{"rows": 6, "cols": 6}"#;
+            write!(
+                stream,
+                "POST /games HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+
+        let mut service = service();
+        let (stream, _) = listener.accept().unwrap();
+        service.handle_connection(stream).unwrap();
+
+        let response = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 201 Created\r\n"));
+        let body = Value::parse(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(Some(6), body.get("rows").and_then(Value::as_usize));
+    }
+}
@@ -0,0 +1,367 @@
+//! Sequential probability ratio tests between two players.
+//!
+//! A test plays pairs of games between a new player and a baseline: both games of a pair start from the same
+//! opening, with colors swapped, so a lopsided opening can't decide the result by itself. After every pair the log
+//! likelihood ratio (LLR) of two hypotheses is updated:
+//!
+//! * H0: the new player is `elo0` Elo stronger than the baseline (usually 0, no better)
+//! * H1: the new player is `elo1` Elo stronger
+//!
+//! The test stops as soon as the LLR leaves the range set by `alpha` (the chance of accepting H1 when H0 is true)
+//! and `beta` (the chance of accepting H0 when H1 is true). Pairs are scored with the pentanomial model: each pair is
+//! worth 0, 0.5, 1, 1.5 or 2 points, which keeps the test honest when the openings favor one color.
+
+use crate::openings::Opening;
+use crate::player::spec::PlayerSpec;
+use crate::rules::Rules;
+use crate::tournament::{elo_with_error, Pairing, Tournament};
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// The bounds and error rates of a test.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Self {
+            elo0: 0.0,
+            elo1: 10.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+/// The hypothesis a test accepted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The new player is `elo0` Elo stronger, so it doesn't have the `elo1` gain being tested for.
+    H0,
+    /// The new player is `elo1` Elo stronger.
+    H1,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Result<Self, String> {
+        if elo0.is_nan() || elo1.is_nan() || elo0 >= elo1 {
+            return Err(format!("elo0 ({}) must be below elo1 ({})", elo0, elo1));
+        }
+        for (name, value) in [("alpha", alpha), ("beta", beta)] {
+            if !(value > 0.0 && value < 0.5) {
+                return Err(format!("{} must be between 0 and 0.5, not {}", name, value));
+            }
+        }
+        Ok(Self {
+            elo0,
+            elo1,
+            alpha,
+            beta,
+        })
+    }
+
+    /// The LLR at which H0 and H1 are accepted.
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    /// # The log likelihood ratio of H1 against H0 after `pairs`
+    ///
+    /// Uses the normal approximation of the generalized SPRT, which only needs the mean and variance of the pair
+    /// scores.
+    ///
+    /// ```
+    /// # use othlib::sprt::{Pentanomial, Sprt};
+    /// let sprt = Sprt::default();
+    /// let even = Pentanomial([10, 20, 40, 20, 10]);
+    /// let strong = Pentanomial([5, 15, 40, 25, 15]);
+    /// assert!(sprt.llr(&even) < 0.0);
+    /// assert!(sprt.llr(&strong) > sprt.llr(&even));
+    /// ```
+    pub fn llr(&self, pairs: &Pentanomial) -> f64 {
+        // Half a pair at each extreme keeps a player that wins (or loses) every pair from leaving the variance at 0
+        let mut counts = pairs.0.map(|x| x as f64);
+        counts[0] += 0.5;
+        counts[4] += 0.5;
+        let (mean, variance) = mean_and_variance(&counts);
+        let (score0, score1) = (expected_score(self.elo0), expected_score(self.elo1));
+        counts.iter().sum::<f64>() * (score1 - score0) * (2.0 * mean - score0 - score1)
+            / (2.0 * variance)
+    }
+
+    /// The accepted hypothesis, if the LLR has crossed a bound.
+    pub fn verdict(&self, llr: f64) -> Option<Verdict> {
+        let (lower, upper) = self.bounds();
+        if llr <= lower {
+            Some(Verdict::H0)
+        } else if llr >= upper {
+            Some(Verdict::H1)
+        } else {
+            None
+        }
+    }
+}
+
+/// The expected score of a player `elo` stronger than its opponent.
+pub fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// How many pairs the new player scored 0, 0.5, 1, 1.5 and 2 points in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pentanomial(pub [usize; 5]);
+
+impl Pentanomial {
+    pub fn count(&self) -> usize {
+        self.0.iter().sum()
+    }
+
+    /// Adds a pair worth `points` (0 to 2 in half point steps).
+    pub fn add(&mut self, points: f64) {
+        self.0[(points * 2.0).round().clamp(0.0, 4.0) as usize] += 1;
+    }
+
+    /// The mean and variance of the pair scores, scaled to a single game (0 to 1).
+    pub fn mean_and_variance(&self) -> (f64, f64) {
+        mean_and_variance(&self.0.map(|x| x as f64))
+    }
+}
+
+fn mean_and_variance(counts: &[f64; 5]) -> (f64, f64) {
+    let count = counts.iter().sum::<f64>();
+    if count == 0.0 {
+        return (0.5, 0.0);
+    }
+    let scores = counts.iter().enumerate().map(|(x, &n)| (x as f64 / 4.0, n));
+    let mean = scores.clone().map(|(score, n)| score * n).sum::<f64>() / count;
+    let variance = scores
+        .map(|(score, n)| n * (score - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    (mean, variance)
+}
+
+/// Where a test stands after some number of pairs.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Status {
+    pub pairs: Pentanomial,
+    /// Wins, draws and losses of the new player, counting games rather than pairs.
+    pub games: (usize, usize, usize),
+    pub llr: f64,
+    pub bounds: (f64, f64),
+    pub verdict: Option<Verdict>,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (wins, draws, losses) = self.games;
+        let (elo, error) = elo_with_error(wins, draws, losses);
+        write!(
+            f,
+            "Games: {} W: {} D: {} L: {} Elo: {:+.1} ± {:.1} LLR: {:.2} ({:.2}, {:.2})",
+            wins + draws + losses,
+            wins,
+            draws,
+            losses,
+            elo,
+            error,
+            self.llr,
+            self.bounds.0,
+            self.bounds.1
+        )?;
+        match self.verdict {
+            Some(Verdict::H0) => write!(f, " H0 accepted"),
+            Some(Verdict::H1) => write!(f, " H1 accepted"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// # A test between a new player and a baseline
+///
+/// The new player is player one of `players`.
+#[derive(Clone, Debug)]
+pub struct Match {
+    pub players: (PlayerSpec, PlayerSpec),
+    pub rows: usize,
+    pub cols: usize,
+    pub sprt: Sprt,
+    /// Pairs cycle through these. An empty opening starts from the usual position.
+    pub openings: Vec<Opening>,
+    /// Stop without a verdict after this many pairs.
+    pub max_pairs: Option<usize>,
+    pub rules: Rules,
+    pub threads: usize,
+}
+
+impl Match {
+    pub fn new(players: (PlayerSpec, PlayerSpec), rows: usize, cols: usize, sprt: Sprt) -> Self {
+        Self {
+            players,
+            rows,
+            cols,
+            sprt,
+            openings: vec![vec![]],
+            max_pairs: None,
+            rules: Rules::default(),
+            threads: thread::available_parallelism().map_or(1, |x| x.get()),
+        }
+    }
+
+    /// Plays pairs across `threads` worker threads until the test accepts a hypothesis or runs out of pairs,
+    /// calling `progress` after every pair.
+    ///
+    /// Pairs still being played when the test stops are thrown away.
+    pub fn run<F: FnMut(&Status)>(&self, mut progress: F) -> Result<Status, String> {
+        if self.openings.is_empty() {
+            return Err("A match needs at least one opening.".to_string());
+        }
+        let mut tournament = Tournament::new(
+            vec![self.players.0.clone(), self.players.1.clone()],
+            self.rows,
+            self.cols,
+        );
+        tournament.openings = self.openings.clone();
+        tournament.rules = self.rules.clone();
+        let tournament = &tournament;
+
+        let max_pairs = self.max_pairs.unwrap_or(usize::MAX);
+        let threads = self.threads.clamp(1, max_pairs.max(1));
+        let (next, stop) = (&AtomicUsize::new(0), &AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let mut status = Status {
+            pairs: Pentanomial::default(),
+            games: (0, 0, 0),
+            llr: 0.0,
+            bounds: self.sprt.bounds(),
+            verdict: None,
+        };
+
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                let sender = sender.clone();
+                scope.spawn(move || loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= max_pairs || stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let opening = index % tournament.openings.len();
+                    let pair = [(0, 1), (1, 0)].map(|(black, white)| {
+                        tournament.play_game(Pairing {
+                            black,
+                            white,
+                            opening,
+                        })
+                    });
+                    if sender.send(pair).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            for pair in receiver {
+                if status.verdict.is_some() {
+                    continue;
+                }
+                let pair = match pair {
+                    [Ok(first), Ok(second)] => [first, second],
+                    [Err(err), _] | [_, Err(err)] => {
+                        stop.store(true, Ordering::Relaxed);
+                        return Err(err);
+                    }
+                };
+
+                let mut points = 0.0;
+                for game in &pair {
+                    let score = game.points(0);
+                    points += score;
+                    match score {
+                        x if x > 0.5 => status.games.0 += 1,
+                        x if x < 0.5 => status.games.2 += 1,
+                        _ => status.games.1 += 1,
+                    }
+                }
+                status.pairs.add(points);
+                status.llr = self.sprt.llr(&status.pairs);
+                status.verdict = self.sprt.verdict(status.llr);
+                if status.verdict.is_some() {
+                    stop.store(true, Ordering::Relaxed);
+                }
+                progress(&status);
+            }
+            Ok(())
+        })?;
+
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::openings::Generator;
+    use crate::player::spec::PlayerKind;
+
+    #[test]
+    fn bounds_follow_error_rates() {
+        let (lower, upper) = Sprt::default().bounds();
+        assert!((lower + 2.944).abs() < 0.001);
+        assert!((upper - 2.944).abs() < 0.001);
+        assert!(Sprt::new(5.0, 0.0, 0.05, 0.05).is_err());
+        assert!(Sprt::new(0.0, 5.0, 0.0, 0.05).is_err());
+        assert_eq!((expected_score(100.0) + expected_score(-100.0)), 1.0);
+
+        let sprt = Sprt::default();
+        let mut pairs = Pentanomial::default();
+        for points in [2.0, 1.5, 1.0, 2.0, 0.5] {
+            pairs.add(points);
+        }
+        assert_eq!(Pentanomial([0, 1, 1, 1, 2]), pairs);
+        let (mean, _) = pairs.mean_and_variance();
+        assert!((mean - 0.7).abs() < 1e-9);
+        assert!(sprt.llr(&pairs) > 0.0);
+        assert_eq!(None, sprt.verdict(sprt.llr(&pairs)));
+        assert_eq!(Some(Verdict::H1), sprt.verdict(3.0));
+        assert_eq!(Some(Verdict::H0), sprt.verdict(-3.0));
+    }
+
+    #[test]
+    fn stops_once_a_hypothesis_is_accepted() {
+        let perfect = PlayerSpec::new(PlayerKind::Minimax);
+        let weak = PlayerSpec {
+            level: Some(1),
+            seed: Some(1),
+            ..perfect.clone()
+        };
+        let sprt = Sprt::new(0.0, 200.0, 0.1, 0.1).unwrap();
+        let mut test = Match::new((perfect, weak), 4, 4, sprt);
+        let mut openings = Generator::new(4, 4, 2);
+        openings.seed = 3;
+        test.openings = openings.generate(10).unwrap().openings;
+        test.max_pairs = Some(100);
+        test.threads = 2;
+
+        let mut updates = 0;
+        let status = test.run(|_| updates += 1).unwrap();
+        assert_eq!(updates, status.pairs.count());
+        assert_eq!(
+            2 * updates,
+            status.games.0 + status.games.1 + status.games.2
+        );
+        assert_eq!(Some(Verdict::H1), status.verdict);
+        assert!(status.llr >= status.bounds.1);
+        assert!(status.to_string().ends_with("H1 accepted"));
+    }
+}
@@ -0,0 +1,569 @@
+//! SVG diagrams of boards and games.
+//!
+//! A `Diagram` draws one position: the grid with row and column numbers (row 0 at the bottom, as the text board
+//! prints it), the discs, and optionally the last move, the legal moves and the discs a move flipped. `replay`
+//! turns a game record into one diagram per move, which can be written as numbered files with `write_frames` or as
+//! a single animated SVG with `animate`.
+//!
+//! Hex boards are drawn as hexagons, each row shifted half a cell from the one below, with each row numbered at its
+//! left end and each column under its lowest cell.
+//!
+//! ```
+//! # use othlib::player::minimax::MinimaxPlayer;
+//! # use othlib::svg::Diagram;
+//! # use othlib::Othello;
+//! let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+//! let game = Othello::with_players(&one, &two, 4, 4);
+//! let svg = Diagram::of_game(&game).render();
+//! assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+//! ```
+
+use crate::board::Board;
+use crate::player::minimax::{MinimaxPlayer, PvMove};
+use crate::rules::{Grid, Rules};
+use crate::Othello;
+
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const BOARD_COLOR: &str = "#2e7d4f";
+const LINE_COLOR: &str = "#1b4d30";
+const LABEL_COLOR: &str = "#333333";
+const LAST_MOVE_COLOR: &str = "#e53935";
+const FLIP_COLOR: &str = "#ffb300";
+const BLOCKED_COLOR: &str = "#5d4037";
+
+/// A drawing of one position.
+#[derive(Clone, Debug)]
+pub struct Diagram {
+    board: Board,
+    /// The symbols drawn as dark and light discs. Any other symbol is drawn grey.
+    symbols: (char, char),
+    last_move: Option<(usize, usize)>,
+    legal_moves: Vec<(usize, usize)>,
+    flips: Vec<(usize, usize)>,
+    grid: Grid,
+    cell: usize,
+}
+
+impl Diagram {
+    pub fn new(board: &Board, symbols: (char, char)) -> Self {
+        Self {
+            board: board.clone(),
+            symbols,
+            last_move: None,
+            legal_moves: vec![],
+            flips: vec![],
+            grid: Grid::default(),
+            cell: 40,
+        }
+    }
+
+    /// A diagram of the game's board, with player one's discs dark.
+    pub fn of_game(game: &Othello) -> Self {
+        Self::new(
+            game.board(),
+            (
+                game.symbol_from_player(crate::ActivePlayer::PlayerOne),
+                game.symbol_from_player(crate::ActivePlayer::PlayerTwo),
+            ),
+        )
+        .with_grid(game.grid())
+    }
+
+    /// Draws the board as a grid of another kind of cell.
+    pub fn with_grid(mut self, grid: Grid) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    /// Marks a move with a dot in the middle of its disc.
+    pub fn with_last_move(mut self, last_move: Option<(usize, usize)>) -> Self {
+        self.last_move = last_move;
+        self
+    }
+
+    /// Draws small dots on empty cells, usually the legal moves of the side to move.
+    pub fn with_legal_moves(mut self, moves: Vec<(usize, usize)>) -> Self {
+        self.legal_moves = moves;
+        self
+    }
+
+    /// Rings discs, usually the ones the last move flipped.
+    pub fn with_flips(mut self, flips: Vec<(usize, usize)>) -> Self {
+        self.flips = flips;
+        self
+    }
+
+    /// Sets the width of a cell in pixels (40 by default).
+    pub fn with_cell_size(mut self, cell: usize) -> Self {
+        self.cell = cell.max(8);
+        self
+    }
+
+    /// The image's width and height in pixels, including the margin for coordinates.
+    pub fn size(&self) -> (usize, usize) {
+        let height = match self.grid {
+            // A cube is drawn as its layers stacked in one tall grid
+            Grid::Square | Grid::Cube => self.board.rows() * self.cell,
+            Grid::Hex => (self.board.rows().saturating_sub(1) as f64 * self.row_height()
+                + self.hex_radius() * 2.0)
+                .ceil() as usize,
+        };
+        (
+            self.margin() * 2 + self.board.cols() * self.cell,
+            self.margin() * 2 + height,
+        )
+    }
+
+    /// Writes the diagram as a standalone SVG document.
+    pub fn render(&self) -> String {
+        let mut svg = self.header();
+        svg += &self.background();
+        svg += &self.pieces();
+        svg += "</svg>\n";
+        svg
+    }
+
+    fn margin(&self) -> usize {
+        self.cell * 3 / 4
+    }
+
+    /// The top left corner of a cell. Row 0 is at the bottom.
+    fn corner(&self, row: usize, col: usize) -> (usize, usize) {
+        (
+            self.margin() + col * self.cell,
+            self.margin() + (self.board.rows() - 1 - row) * self.cell,
+        )
+    }
+
+    fn center(&self, row: usize, col: usize) -> (f64, f64) {
+        if self.grid == Grid::Hex {
+            return self.hex_center(row as isize, col as isize);
+        }
+        let (x, y) = self.corner(row, col);
+        let half = self.cell as f64 / 2.0;
+        (x as f64 + half, y as f64 + half)
+    }
+
+    /// The distance from the center of a hexagon to its corners. Hexagons are a cell wide from side to side.
+    fn hex_radius(&self) -> f64 {
+        self.cell as f64 / 3f64.sqrt()
+    }
+
+    /// How far apart rows of hexagons are.
+    fn row_height(&self) -> f64 {
+        self.hex_radius() * 1.5
+    }
+
+    /// The center of a hexagon, which can be off the board to place labels next to it. The middle row starts at
+    /// the left margin.
+    fn hex_center(&self, row: isize, col: isize) -> (f64, f64) {
+        let rows = self.board.rows() as isize;
+        let across = col as f64 + (row - rows / 2) as f64 / 2.0;
+        (
+            self.margin() as f64 + (across + 0.5) * self.cell as f64,
+            self.margin() as f64 + (rows - 1 - row) as f64 * self.row_height() + self.hex_radius(),
+        )
+    }
+
+    fn header(&self) -> String {
+        let (width, height) = self.size();
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" \
+             font-family=\"sans-serif\" font-size=\"{2}\">\n",
+            width,
+            height,
+            self.cell * 2 / 5
+        )
+    }
+
+    /// The board, grid lines and coordinates, which don't change during a game.
+    fn background(&self) -> String {
+        if self.grid == Grid::Hex {
+            return self.hex_background();
+        }
+        let (rows, cols, cell, margin) = (
+            self.board.rows(),
+            self.board.cols(),
+            self.cell,
+            self.margin(),
+        );
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+            margin,
+            margin,
+            cols * cell,
+            rows * cell,
+            BOARD_COLOR
+        )
+        .expect("Writing to a string can't fail");
+
+        svg += &format!("<g stroke=\"{}\" stroke-width=\"1\">\n", LINE_COLOR);
+        for line in 0..=rows {
+            let y = margin + line * cell;
+            writeln!(
+                svg,
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>",
+                margin,
+                y,
+                margin + cols * cell,
+                y
+            )
+            .expect("Writing to a string can't fail");
+        }
+        for line in 0..=cols {
+            let x = margin + line * cell;
+            writeln!(
+                svg,
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>",
+                x,
+                margin,
+                x,
+                margin + rows * cell
+            )
+            .expect("Writing to a string can't fail");
+        }
+        svg += "</g>\n";
+
+        // Cells that aren't part of the board are filled in over the grid
+        for row in 0..rows {
+            for col in 0..cols {
+                if self.board.is_blocked(row, col) {
+                    let (x, y) = self.corner(row, col);
+                    writeln!(
+                        svg,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                        x, y, cell, cell, BLOCKED_COLOR
+                    )
+                    .expect("Writing to a string can't fail");
+                }
+            }
+        }
+
+        svg += &format!(
+            "<g fill=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">\n",
+            LABEL_COLOR
+        );
+        for row in 0..rows {
+            let (_, y) = self.center(row, 0);
+            writeln!(svg, "<text x=\"{}\" y=\"{}\">{}</text>", margin / 2, y, row)
+                .expect("Writing to a string can't fail");
+        }
+        for col in 0..cols {
+            let (x, _) = self.center(0, col);
+            writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\">{}</text>",
+                x,
+                margin + rows * cell + margin / 2,
+                col
+            )
+            .expect("Writing to a string can't fail");
+        }
+        svg += "</g>\n";
+        svg
+    }
+
+    /// The hexagons of the open cells and their coordinates.
+    fn hex_background(&self) -> String {
+        let (rows, cols) = (self.board.rows(), self.board.cols());
+        let radius = self.hex_radius();
+        let mut svg = format!(
+            "<g fill=\"{}\" stroke=\"{}\" stroke-width=\"1\">\n",
+            BOARD_COLOR, LINE_COLOR
+        );
+        for row in 0..rows {
+            for col in (0..cols).filter(|&col| !self.board.is_blocked(row, col)) {
+                let (x, y) = self.center(row, col);
+                // Pointy side up, so neighbors in a row share a vertical side
+                let corners = (0..6)
+                    .map(|corner| {
+                        let angle =
+                            std::f64::consts::PI / 3.0 * corner as f64 + std::f64::consts::PI / 6.0;
+                        format!(
+                            "{:.1},{:.1}",
+                            x + radius * angle.cos(),
+                            y + radius * angle.sin()
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                writeln!(svg, "<polygon points=\"{}\"/>", corners.join(" "))
+                    .expect("Writing to a string can't fail");
+            }
+        }
+        svg += "</g>\n";
+
+        svg += &format!(
+            "<g fill=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">\n",
+            LABEL_COLOR
+        );
+        for row in 0..rows {
+            if let Some(first) = (0..cols).find(|&col| !self.board.is_blocked(row, col)) {
+                let (x, y) = self.hex_center(row as isize, first as isize - 1);
+                writeln!(svg, "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>", x, y, row)
+                    .expect("Writing to a string can't fail");
+            }
+        }
+        for col in 0..cols {
+            if let Some(lowest) = (0..rows).find(|&row| !self.board.is_blocked(row, col)) {
+                let (x, y) = self.hex_center(lowest as isize - 1, col as isize);
+                writeln!(svg, "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>", x, y, col)
+                    .expect("Writing to a string can't fail");
+            }
+        }
+        svg += "</g>\n";
+        svg
+    }
+
+    /// The discs and markers.
+    fn pieces(&self) -> String {
+        let radius = self.cell as f64 * 0.4;
+        let mut svg = String::new();
+        for row in 0..self.board.rows() {
+            for col in 0..self.board.cols() {
+                let (x, y) = self.center(row, col);
+                match self.board.get_cell(row, col) {
+                    Some(symbol) => {
+                        let (fill, stroke) = match symbol {
+                            x if x == self.symbols.0 => ("#111111", "#000000"),
+                            x if x == self.symbols.1 => ("#f5f5f5", "#9e9e9e"),
+                            _ => ("#9e9e9e", "#616161"),
+                        };
+                        writeln!(
+                            svg,
+                            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"{}\"/>",
+                            x, y, radius, fill, stroke
+                        )
+                        .expect("Writing to a string can't fail");
+                    }
+                    None if self.legal_moves.contains(&(row, col)) => {
+                        writeln!(
+                            svg,
+                            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#000000\" fill-opacity=\"0.3\"/>",
+                            x,
+                            y,
+                            radius / 4.0
+                        )
+                        .expect("Writing to a string can't fail");
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        for &(row, col) in &self.flips {
+            let (x, y) = self.center(row, col);
+            writeln!(
+                svg,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>",
+                x,
+                y,
+                radius + self.cell as f64 * 0.04,
+                FLIP_COLOR,
+                self.cell as f64 * 0.06
+            )
+            .expect("Writing to a string can't fail");
+        }
+        if let Some((row, col)) = self.last_move {
+            let (x, y) = self.center(row, col);
+            writeln!(
+                svg,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+                x,
+                y,
+                radius / 4.0,
+                LAST_MOVE_COLOR
+            )
+            .expect("Writing to a string can't fail");
+        }
+        svg
+    }
+}
+
+/// # Replays a game record into a diagram per position
+///
+/// The first diagram is the starting position and each following one shows the board after a move, with the move
+/// marked and the discs it flipped ringed. `legal_moves` adds the next player's legal moves to every diagram. A
+/// player without moves passes, whether or not the record says `pass`.
+///
+/// ```
+/// # use othlib::rules::Rules;
+/// # use othlib::svg::replay;
+/// let rules = Rules::default();
+/// let frames = replay(&rules, 4, 4, ('X', 'O'), &[Some((0, 2)), Some((0, 1))], true).unwrap();
+/// assert_eq!(3, frames.len());
+/// assert!(replay(&rules, 4, 4, ('X', 'O'), &[Some((0, 0))], false).is_err());
+/// ```
+pub fn replay(
+    rules: &Rules,
+    rows: usize,
+    cols: usize,
+    symbols: (char, char),
+    moves: &[PvMove],
+    legal_moves: bool,
+) -> Result<Vec<Diagram>, String> {
+    let (one, two) = (MinimaxPlayer::new(symbols.0), MinimaxPlayer::new(symbols.1));
+    let mut game = rules.start(&one, &two, rows, cols)?;
+    let legal = |game: &Othello| {
+        if legal_moves {
+            game.successors(game.get_active_symbol())
+        } else {
+            vec![]
+        }
+    };
+    let mut frames = vec![Diagram::of_game(&game).with_legal_moves(legal(&game))];
+
+    for (ply, step) in moves.iter().enumerate() {
+        if game.has_more_moves() && !game.symbol_has_more_moves(game.get_active_symbol()) {
+            game.change_active_player();
+        }
+        let (row, col) = match step {
+            Some(step) => *step,
+            // Passes were already taken care of above
+            None if !game.symbol_has_more_moves(game.get_active_symbol()) => continue,
+            None => {
+                return Err(format!(
+                    "Move {} passes when there are legal moves.",
+                    ply + 1
+                ))
+            }
+        };
+        let flips = game.flips(row, col, game.get_active_symbol());
+        game.try_move(row, col)
+            .map_err(|err| format!("Move {}: {}", ply + 1, err))?;
+        if game.has_more_moves() && !game.symbol_has_more_moves(game.get_active_symbol()) {
+            game.change_active_player();
+        }
+        frames.push(
+            Diagram::of_game(&game)
+                .with_last_move(Some((row, col)))
+                .with_flips(flips)
+                .with_legal_moves(legal(&game)),
+        );
+    }
+    Ok(frames)
+}
+
+/// # Combines diagrams into one animated SVG
+///
+/// Each diagram is shown for `seconds` before the next, and the animation loops. The diagrams should all be the
+/// same size, as they are when they come from `replay`.
+///
+/// ```
+/// # use othlib::rules::Rules;
+/// # use othlib::svg::{animate, replay};
+/// let frames = replay(&Rules::default(), 4, 4, ('X', 'O'), &[Some((0, 2))], false).unwrap();
+/// let svg = animate(&frames, 1.0);
+/// assert_eq!(2, svg.matches("<animate ").count());
+/// ```
+pub fn animate(frames: &[Diagram], seconds: f64) -> String {
+    let first = match frames.first() {
+        Some(first) => first,
+        None => return String::new(),
+    };
+    let mut svg = first.header();
+    svg += &first.background();
+    let count = frames.len() as f64;
+    for (index, frame) in frames.iter().enumerate() {
+        // Each frame is only visible during its share of the loop
+        writeln!(
+            svg,
+            "<g visibility=\"hidden\">\n<animate attributeName=\"visibility\" values=\"hidden;visible;hidden\" \
+             keyTimes=\"0;{:.6};{:.6}\" dur=\"{}s\" calcMode=\"discrete\" repeatCount=\"indefinite\"/>",
+            index as f64 / count,
+            (index + 1) as f64 / count,
+            seconds * count
+        )
+        .expect("Writing to a string can't fail");
+        svg += &frame.pieces();
+        svg += "</g>\n";
+    }
+    svg += "</svg>\n";
+    svg
+}
+
+/// Writes each diagram to `directory` as `frame-000.svg`, `frame-001.svg` and so on, returning the paths.
+pub fn write_frames<P: AsRef<Path>>(directory: P, frames: &[Diagram]) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(&directory)?;
+    let digits = frames.len().saturating_sub(1).to_string().len().max(3);
+    frames
+        .iter()
+        .enumerate()
+        .map(|(index, frame)| {
+            let path =
+                directory
+                    .as_ref()
+                    .join(format!("frame-{:0digits$}.svg", index, digits = digits));
+            fs::write(&path, frame.render())?;
+            Ok(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn draws_discs_and_markers() {
+        let board = Board::decode("..X./.XX./.OX./....").unwrap();
+        let diagram = Diagram::new(&board, ('X', 'O'))
+            .with_last_move(Some((0, 2)))
+            .with_flips(vec![(1, 2)])
+            .with_legal_moves(vec![(0, 1), (2, 0)])
+            .with_cell_size(20);
+        assert_eq!((110, 110), diagram.size());
+
+        let svg = diagram.render();
+        assert_eq!(4, svg.matches("fill=\"#111111\"").count());
+        assert_eq!(1, svg.matches("fill=\"#f5f5f5\"").count());
+        assert_eq!(2, svg.matches("fill-opacity").count());
+        assert_eq!(1, svg.matches(FLIP_COLOR).count());
+        assert_eq!(1, svg.matches(LAST_MOVE_COLOR).count());
+        // Row 0 is drawn at the bottom, under row 3
+        assert!(svg.contains("<text x=\"7\" y=\"25\">3</text>"));
+        assert!(svg.contains("<text x=\"7\" y=\"85\">0</text>"));
+        // The last move sits in the bottom row
+        let (x, y) = diagram.center(0, 2);
+        assert_eq!((65.0, 85.0), (x, y));
+    }
+
+    #[test]
+    fn draws_hex_boards_as_hexagons() {
+        let rules = Rules {
+            grid: Grid::Hex,
+            ..Rules::default()
+        };
+        let frames = replay(&rules, 5, 5, ('X', 'O'), &[Some((1, 1))], false).unwrap();
+        let diagram = frames[1].clone().with_cell_size(20);
+        assert_eq!((130, 123), diagram.size());
+
+        let svg = diagram.render();
+        assert_eq!(19, svg.matches("<polygon").count());
+        // Each row is half a cell to the right of the one below it
+        assert_eq!(35.0, diagram.center(1, 1).0);
+        assert_eq!(diagram.center(1, 1).0 + 10.0, diagram.center(2, 1).0);
+        assert_eq!(diagram.center(1, 1).0 + 20.0, diagram.center(1, 2).0);
+    }
+
+    #[test]
+    fn replays_passes_and_writes_frames() {
+        let moves = [Some((0, 2)), Some((0, 1)), Some((0, 0))];
+        let frames = replay(&Rules::default(), 4, 4, ('X', 'O'), &moves, false).unwrap();
+        assert_eq!(4, frames.len());
+        assert_eq!(Some((0, 0)), frames[3].last_move);
+        assert!(!frames[3].flips.is_empty());
+        assert!(replay(&Rules::default(), 4, 4, ('X', 'O'), &[None], false).is_err());
+
+        let directory = std::env::temp_dir().join(format!("othlib-svg-{}", std::process::id()));
+        let paths = write_frames(&directory, &frames).unwrap();
+        assert_eq!(directory.join("frame-003.svg"), paths[3]);
+        assert_eq!(frames[2].render(), fs::read_to_string(&paths[2]).unwrap());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,468 @@
+//! Round-robin tournaments between bots.
+//!
+//! Every pair of players meets once per opening with each player taking `X` in turn, so neither player gets the
+//! better side of an opening more often than the other. Games run in parallel and end with a table of results:
+//!
+//! ```text
+//! Rank  Player     Games     W     D     L  Score   Discs  Elo
+//!    1  minimax:4      8     6     0     2  75.0%   +10.5  +191 ± 240
+//!    2  minimax:2      8     2     0     6  25.0%   -10.5  -191 ± 240
+//! ```
+//!
+//! Elo is relative to the average player in the tournament, with a 95% confidence interval.
+
+use crate::eval::disc_differential;
+use crate::openings::{play_opening, Opening};
+use crate::player::spec::PlayerSpec;
+use crate::rules::Rules;
+
+use std::fmt::Write;
+use std::sync::mpsc;
+use std::thread;
+
+/// One game of a tournament.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Pairing {
+    /// The index of the player playing `X`.
+    pub black: usize,
+    /// The index of the player playing `O`.
+    pub white: usize,
+    pub opening: usize,
+}
+
+/// The outcome of a tournament game.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GameResult {
+    pub pairing: Pairing,
+    /// 1 if `X` won, 2 if `O` won and 0 for a tie, as `Othello::get_winner_number` reports it.
+    pub winner: usize,
+    /// `X`'s discs minus `O`'s at the end of the game.
+    pub differential: isize,
+}
+
+impl GameResult {
+    /// The points `player` scored in this game: 1 for a win, 0.5 for a tie and 0 for a loss.
+    pub fn points(&self, player: usize) -> f64 {
+        match self.winner {
+            0 => 0.5,
+            1 if player == self.pairing.black => 1.0,
+            2 if player == self.pairing.white => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// The final disc differential from `player`'s side.
+    pub fn differential_for(&self, player: usize) -> isize {
+        if player == self.pairing.black {
+            self.differential
+        } else {
+            -self.differential
+        }
+    }
+}
+
+/// # Tournament configuration
+#[derive(Clone, Debug)]
+pub struct Tournament {
+    pub players: Vec<PlayerSpec>,
+    pub rows: usize,
+    pub cols: usize,
+    /// Every pairing plays each of these twice. An empty opening starts from the usual position.
+    pub openings: Vec<Opening>,
+    pub rules: Rules,
+    pub threads: usize,
+}
+
+impl Tournament {
+    pub fn new(players: Vec<PlayerSpec>, rows: usize, cols: usize) -> Self {
+        Self {
+            players,
+            rows,
+            cols,
+            openings: vec![vec![]],
+            rules: Rules::default(),
+            threads: thread::available_parallelism().map_or(1, |x| x.get()),
+        }
+    }
+
+    /// Every game of the tournament: each pair of players, each opening, each player as `X`.
+    pub fn schedule(&self) -> Vec<Pairing> {
+        let mut schedule = vec![];
+        for opening in 0..self.openings.len() {
+            for first in 0..self.players.len() {
+                for second in first + 1..self.players.len() {
+                    schedule.push(Pairing {
+                        black: first,
+                        white: second,
+                        opening,
+                    });
+                    schedule.push(Pairing {
+                        black: second,
+                        white: first,
+                        opening,
+                    });
+                }
+            }
+        }
+        schedule
+    }
+
+    /// Plays a single game to the end.
+    pub fn play_game(&self, pairing: Pairing) -> Result<GameResult, String> {
+        let black = self.players[pairing.black].build('X')?;
+        let white = self.players[pairing.white].build('O')?;
+        let mut game = self.rules.start(&*black, &*white, self.rows, self.cols)?;
+        play_opening(&mut game, &self.openings[pairing.opening])?;
+
+        let winner = game.run_silent();
+        Ok(GameResult {
+            pairing,
+            winner,
+            differential: disc_differential(&game, 'X'),
+        })
+    }
+
+    /// Plays every game across `threads` worker threads, calling `progress` as each one finishes.
+    pub fn run<F: FnMut(&GameResult)>(&self, mut progress: F) -> Result<Standings, String> {
+        if self.players.len() < 2 {
+            return Err("A tournament needs at least two players.".to_string());
+        }
+        // Fail before spawning anything if a player can't be built (e.g. a missing weight file) or an opening
+        // doesn't fit the board
+        for player in &self.players {
+            player.build('X')?;
+        }
+        let (one, two) = (self.players[0].build('X')?, self.players[1].build('O')?);
+        let start = self.rules.start(&*one, &*two, self.rows, self.cols)?;
+        for opening in &self.openings {
+            play_opening(&mut start.clone(), opening)?;
+        }
+
+        let schedule = self.schedule();
+        let threads = self.threads.clamp(1, schedule.len().max(1));
+        let (sender, receiver) = mpsc::channel();
+        let mut games = Vec::with_capacity(schedule.len());
+
+        thread::scope(|scope| {
+            for worker in 0..threads {
+                let sender = sender.clone();
+                let schedule = &schedule;
+                scope.spawn(move || {
+                    for index in (worker..schedule.len()).step_by(threads) {
+                        if sender
+                            .send((index, self.play_game(schedule[index])))
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            for (index, result) in receiver {
+                let result = result?;
+                progress(&result);
+                games.push((index, result));
+            }
+            Ok::<(), String>(())
+        })?;
+
+        games.sort_by_key(|(index, _)| *index);
+        Ok(Standings {
+            names: self.players.iter().map(ToString::to_string).collect(),
+            games: games.into_iter().map(|(_, result)| result).collect(),
+        })
+    }
+}
+
+/// The results of a tournament.
+#[derive(Clone, Debug)]
+pub struct Standings {
+    pub names: Vec<String>,
+    pub games: Vec<GameResult>,
+}
+
+/// One player's line in the standings.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Record {
+    pub player: usize,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    /// The average final disc differential.
+    pub discs: f64,
+    /// Elo relative to the average player.
+    pub elo: f64,
+    /// Half the width of the (approximate) 95% confidence interval for `elo`.
+    pub error: f64,
+}
+
+impl Record {
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    /// The fraction of the available points scored.
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games().max(1) as f64
+    }
+}
+
+/// The Elo difference that gives an expected score of `score`.
+pub fn elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// An Elo estimate and the half width of its 95% confidence interval from a win, draw and loss count.
+///
+/// Perfect and zero scores give infinite ratings.
+///
+/// ```
+/// # use othlib::tournament::elo_with_error;
+/// let (elo, error) = elo_with_error(30, 40, 30);
+/// assert_eq!(0.0, elo);
+/// assert!(error > 40.0 && error < 60.0);
+/// assert_eq!(f64::INFINITY, elo_with_error(3, 0, 0).0);
+/// ```
+pub fn elo_with_error(wins: usize, draws: usize, losses: usize) -> (f64, f64) {
+    let games = (wins + draws + losses) as f64;
+    if games == 0.0 {
+        return (0.0, f64::INFINITY);
+    }
+    let score = (wins as f64 + draws as f64 / 2.0) / games;
+    if score == 0.0 || score == 1.0 {
+        return (elo(score), f64::INFINITY);
+    }
+    let variance = (wins as f64 * (1.0 - score).powi(2)
+        + draws as f64 * (0.5 - score).powi(2)
+        + losses as f64 * score.powi(2))
+        / games;
+    // The slope of `elo` at `score` turns the score's margin into an Elo margin
+    let margin = 1.96 * (variance / games).sqrt();
+    let slope = 400.0 / (std::f64::consts::LN_10 * score * (1.0 - score));
+    (elo(score), margin * slope)
+}
+
+impl Standings {
+    /// Each player's record, best score first.
+    pub fn records(&self) -> Vec<Record> {
+        let mut records = (0..self.names.len())
+            .map(|player| {
+                let games = self
+                    .games
+                    .iter()
+                    .filter(|game| game.pairing.black == player || game.pairing.white == player);
+                let (mut wins, mut draws, mut losses, mut discs) = (0, 0, 0, 0);
+                for game in games {
+                    if game.winner == 0 {
+                        draws += 1;
+                    } else if game.points(player) > 0.0 {
+                        wins += 1;
+                    } else {
+                        losses += 1;
+                    }
+                    discs += game.differential_for(player);
+                }
+                let (elo, error) = elo_with_error(wins, draws, losses);
+                Record {
+                    player,
+                    wins,
+                    draws,
+                    losses,
+                    discs: discs as f64 / (wins + draws + losses).max(1) as f64,
+                    elo,
+                    error,
+                }
+            })
+            .collect::<Vec<_>>();
+        records.sort_by(|a, b| {
+            b.score()
+                .total_cmp(&a.score())
+                .then(b.discs.total_cmp(&a.discs))
+        });
+        records
+    }
+
+    /// The points `player` scored against `opponent` and the games they played.
+    pub fn head_to_head(&self, player: usize, opponent: usize) -> (f64, usize) {
+        self.games
+            .iter()
+            .filter(|game| {
+                (game.pairing.black, game.pairing.white) == (player, opponent)
+                    || (game.pairing.black, game.pairing.white) == (opponent, player)
+            })
+            .fold((0.0, 0), |(points, games), game| {
+                (points + game.points(player), games + 1)
+            })
+    }
+
+    /// The standings followed by the crosstable, for the terminal.
+    pub fn table(&self) -> String {
+        let records = self.records();
+        let width = self.names.iter().map(String::len).max().unwrap_or(0).max(6);
+        let mut table = String::new();
+
+        writeln!(
+            table,
+            "Rank  {:width$}  Games     W     D     L  Score   Discs  Elo",
+            "Player",
+            width = width
+        )
+        .expect("Writing to a string can't fail");
+        for (rank, record) in records.iter().enumerate() {
+            writeln!(
+                table,
+                "{:>4}  {:width$}  {:>5} {:>5} {:>5} {:>5} {:>5.1}% {:>+7.1}  {}",
+                rank + 1,
+                self.names[record.player],
+                record.games(),
+                record.wins,
+                record.draws,
+                record.losses,
+                record.score() * 100.0,
+                record.discs,
+                format_elo(record.elo, record.error),
+                width = width
+            )
+            .expect("Writing to a string can't fail");
+        }
+
+        // Rows and columns follow the ranking, so the strongest results sit in the top left
+        write!(table, "\n      {:width$}", "", width = width)
+            .expect("Writing to a string can't fail");
+        for rank in 1..=records.len() {
+            write!(table, " {:>7}", rank).expect("Writing to a string can't fail");
+        }
+        for (rank, record) in records.iter().enumerate() {
+            write!(
+                table,
+                "\n{:>4}  {:width$}",
+                rank + 1,
+                self.names[record.player],
+                width = width
+            )
+            .expect("Writing to a string can't fail");
+            for opponent in &records {
+                let cell = if opponent.player == record.player {
+                    "-".to_string()
+                } else {
+                    let (points, games) = self.head_to_head(record.player, opponent.player);
+                    format!("{}/{}", points, games)
+                };
+                write!(table, " {:>7}", cell).expect("Writing to a string can't fail");
+            }
+        }
+        table.push('\n');
+        table
+    }
+
+    /// The standings as CSV, one row per player with a crosstable column for each opponent in the same order.
+    pub fn to_csv(&self) -> String {
+        let records = self.records();
+        let mut csv = "rank,player,games,wins,draws,losses,score,discs,elo,error".to_string();
+        for rank in 1..=records.len() {
+            write!(csv, ",vs {}", rank).expect("Writing to a string can't fail");
+        }
+        for (rank, record) in records.iter().enumerate() {
+            write!(
+                csv,
+                "\n{},{},{},{},{},{},{:.4},{:.2},{:.1},{:.1}",
+                rank + 1,
+                csv_field(&self.names[record.player]),
+                record.games(),
+                record.wins,
+                record.draws,
+                record.losses,
+                record.score(),
+                record.discs,
+                record.elo,
+                record.error
+            )
+            .expect("Writing to a string can't fail");
+            for opponent in &records {
+                if opponent.player == record.player {
+                    csv.push(',');
+                } else {
+                    let (points, _) = self.head_to_head(record.player, opponent.player);
+                    write!(csv, ",{}", points).expect("Writing to a string can't fail");
+                }
+            }
+        }
+        csv.push('\n');
+        csv
+    }
+}
+
+fn format_elo(elo: f64, error: f64) -> String {
+    if elo.is_infinite() {
+        return if elo > 0.0 { "+inf" } else { "-inf" }.to_string();
+    }
+    if error.is_infinite() {
+        return format!("{:+.0} ± inf", elo);
+    }
+    format!("{:+.0} ± {:.0}", elo, error)
+}
+
+/// Quotes specs like `minimax:depth=4,seed=1` that contain commas.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::openings::Generator;
+    use crate::player::spec::PlayerKind;
+
+    fn depth(depth: usize) -> PlayerSpec {
+        PlayerSpec {
+            depth: Some(depth),
+            ..PlayerSpec::new(PlayerKind::Minimax)
+        }
+    }
+
+    #[test]
+    fn plays_every_pairing_with_both_colors() {
+        let mut tournament = Tournament::new(vec![depth(1), depth(2), depth(3)], 4, 4);
+        let mut openings = Generator::new(4, 4, 2);
+        openings.seed = 7;
+        tournament.openings = openings.generate(2).unwrap().openings;
+        tournament.threads = 3;
+        assert_eq!(2, tournament.openings.len());
+
+        let mut finished = 0;
+        let standings = tournament.run(|_| finished += 1).unwrap();
+        assert_eq!(12, finished);
+        assert_eq!(12, standings.games.len());
+
+        let records = standings.records();
+        assert!(records.iter().all(|record| record.games() == 8));
+        let points = records.iter().map(|x| x.score() * 8.0).sum::<f64>();
+        assert_eq!(12.0, points);
+        let discs = records.iter().map(|x| x.discs).sum::<f64>();
+        assert!(discs.abs() < 1e-9);
+
+        let (points, games) = standings.head_to_head(0, 1);
+        assert_eq!(4, games);
+        assert_eq!(4.0, points + standings.head_to_head(1, 0).0);
+
+        let table = standings.table();
+        assert!(table.contains("minimax:depth=3"));
+        assert_eq!(4, standings.to_csv().lines().count());
+    }
+
+    #[test]
+    fn elo_is_symmetric() {
+        assert_eq!(0.0, elo(0.5));
+        assert!((elo(0.75) - 190.85).abs() < 0.01);
+        assert!((elo(0.75) + elo(0.25)).abs() < 1e-9);
+        assert_eq!(f64::NEG_INFINITY, elo_with_error(0, 0, 5).0);
+        assert_eq!("+inf", format_elo(f64::INFINITY, 0.0));
+        assert_eq!("\"minimax:3,seed=1\"", csv_field("minimax:3,seed=1"));
+    }
+}
@@ -0,0 +1,586 @@
+//! A full-screen terminal interface for human players.
+//!
+//! Instead of typing a row and a column, players move a cursor over the board with the arrow keys (or `w`, `a`, `s`,
+//! `d`) or click a cell, then press Enter or Space to play there. Legal moves are marked with `?` like in the plain
+//! display, and the discs a move would flip light up while the cursor is on it. `u` takes back the last human move
+//! (and the bot replies that followed it), `h` moves the cursor to the move a short search likes best, `p` swaps when
+//! the swap rule allows it, and `q` quits.
+//!
+//! The terminal is switched to raw mode with `stty`, so this only works on a real terminal on Unix-like systems.
+//! `is_supported` says whether it can be used, otherwise stick to `Othello::run`.
+
+use crate::board::{Board, BLOCKED};
+use crate::player::minimax::MinimaxPlayer;
+use crate::{ActivePlayer, Othello};
+
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+/// How deep the search behind the hint key looks.
+const HINT_DEPTH: usize = 4;
+
+/// Screen lines above the first row of the board.
+const TOP: usize = 2;
+
+const CURSOR: &str = "\x1b[7m";
+const FLIP: &str = "\x1b[1;33m";
+const LEGAL: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether the terminal interface can run: both stdin and stdout have to be terminals.
+pub fn is_supported() -> bool {
+    cfg!(unix) && io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Undo,
+    Hint,
+    Swap,
+    Quit,
+    /// A left click at a 1-based screen column and line.
+    Click(usize, usize),
+}
+
+/// # Turns raw terminal input into keys
+///
+/// Understands arrow keys, SGR mouse reports and single letters. Anything else is dropped.
+///
+/// ```
+/// # use othlib::tui::{parse_keys, Key};
+/// assert_eq!(vec![Key::Up, Key::Select, Key::Undo], parse_keys(b"\x1b[A\ru"));
+/// assert_eq!(vec![Key::Click(7, 3)], parse_keys(b"\x1b[<0;7;3M\x1b[<0;7;3m"));
+/// assert_eq!(vec![Key::Quit], parse_keys(&[3]));
+/// ```
+pub fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        match &bytes[index..] {
+            [0x1b, b'[', b'<', rest @ ..] => {
+                // SGR mouse report: ESC [ < button ; x ; y (M for press, m for release)
+                let end = rest.iter().position(|&x| x == b'M' || x == b'm');
+                let end = match end {
+                    Some(end) => end,
+                    None => break,
+                };
+                let fields = String::from_utf8_lossy(&rest[..end])
+                    .split(';')
+                    .map(|x| x.parse::<usize>().ok())
+                    .collect::<Vec<_>>();
+                if let (b'M', [Some(0), Some(x), Some(y)]) = (rest[end], fields.as_slice()) {
+                    keys.push(Key::Click(*x, *y));
+                }
+                index += 3 + end + 1;
+                continue;
+            }
+            [0x1b, b'[', arrow, ..] | [0x1b, b'O', arrow, ..] => {
+                match arrow {
+                    b'A' => keys.push(Key::Up),
+                    b'B' => keys.push(Key::Down),
+                    b'C' => keys.push(Key::Right),
+                    b'D' => keys.push(Key::Left),
+                    _ => {}
+                }
+                index += 3;
+                continue;
+            }
+            [byte, ..] => match byte {
+                b'w' => keys.push(Key::Up),
+                b's' => keys.push(Key::Down),
+                b'a' => keys.push(Key::Left),
+                b'd' => keys.push(Key::Right),
+                b'\r' | b'\n' | b' ' => keys.push(Key::Select),
+                b'u' => keys.push(Key::Undo),
+                b'h' | b'?' => keys.push(Key::Hint),
+                b'p' => keys.push(Key::Swap),
+                // Ctrl-C doesn't send a signal in raw mode
+                b'q' | 3 => keys.push(Key::Quit),
+                _ => {}
+            },
+            [] => {}
+        }
+        index += 1;
+    }
+    keys
+}
+
+/// # The state of a game played in the terminal interface
+///
+/// `humans` says which players take their moves from the keyboard, the others are asked with `Othello::play_turn`.
+pub struct Tui<'a> {
+    game: Othello<'a>,
+    humans: (bool, bool),
+    /// The game before every human move, and how many moves had been played.
+    history: Vec<(Othello<'a>, usize)>,
+    moves: Vec<(char, Option<(usize, usize)>)>,
+    cursor: (usize, usize),
+    message: String,
+}
+
+impl<'a> Tui<'a> {
+    pub fn new(game: Othello<'a>, humans: (bool, bool)) -> Self {
+        let board = game.board();
+        let cursor = (board.rows() / 2, board.cols() / 2);
+        Self {
+            game,
+            humans,
+            history: vec![],
+            moves: vec![],
+            cursor,
+            message: String::new(),
+        }
+    }
+
+    pub fn game(&self) -> &Othello<'a> {
+        &self.game
+    }
+
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    pub fn moves(&self) -> &[(char, Option<(usize, usize)>)] {
+        &self.moves
+    }
+
+    fn is_human(&self, player: ActivePlayer) -> bool {
+        match player {
+            ActivePlayer::PlayerOne => self.humans.0,
+            ActivePlayer::PlayerTwo => self.humans.1,
+        }
+    }
+
+    /// Whether the game is waiting for a key: a human has a move to make, or the game is over.
+    pub fn needs_input(&self) -> bool {
+        let active = self.game.active_player();
+        !self.game.has_more_moves()
+            || (self.is_human(active) && self.game.player_has_more_moves(active))
+    }
+
+    /// Handles a key. Returns `false` when the player wants to quit.
+    pub fn handle(&mut self, key: Key) -> bool {
+        let (rows, cols) = (self.game.board().rows(), self.game.board().cols());
+        let (row, col) = self.cursor;
+        match key {
+            Key::Up => self.cursor.0 = (row + 1).min(rows - 1),
+            Key::Down => self.cursor.0 = row.saturating_sub(1),
+            Key::Right => self.cursor.1 = (col + 1).min(cols - 1),
+            Key::Left => self.cursor.1 = col.saturating_sub(1),
+            Key::Click(x, y) => {
+                if let Some(cell) = self.cell_at(x, y) {
+                    self.cursor = cell;
+                    self.select();
+                }
+            }
+            Key::Select => self.select(),
+            Key::Undo => self.undo(),
+            Key::Hint => self.hint(),
+            Key::Swap => self.swap(),
+            Key::Quit => return false,
+        }
+        true
+    }
+
+    /// Plays the cell under the cursor for the human to move.
+    fn select(&mut self) {
+        if !self.needs_input() || !self.game.has_more_moves() {
+            return;
+        }
+        let (row, col) = self.cursor;
+        let symbol = self.game.get_active_symbol();
+        let before = self.game.clone();
+        match self.game.try_move(row, col) {
+            Ok(()) => {
+                self.history.push((before, self.moves.len()));
+                self.moves.push((symbol, Some((row, col))));
+                self.message.clear();
+            }
+            Err(err) => self.message = err,
+        }
+    }
+
+    /// Takes over the first move for the human to move, if the swap rule allows it.
+    fn swap(&mut self) {
+        if !self.needs_input() {
+            return;
+        }
+        let symbol = self.game.get_active_symbol();
+        let before = self.game.clone();
+        match self.game.swap() {
+            Ok(()) => {
+                self.history.push((before, self.moves.len()));
+                self.message = format!("{} swaps and takes over the first move.", symbol);
+            }
+            Err(err) => self.message = err,
+        }
+    }
+
+    fn undo(&mut self) {
+        match self.history.pop() {
+            Some((game, moves)) => {
+                self.game = game;
+                self.moves.truncate(moves);
+                self.message = "Took back the last move.".to_string();
+            }
+            None => self.message = "There's nothing to take back.".to_string(),
+        }
+    }
+
+    fn hint(&mut self) {
+        if !self.needs_input() || !self.game.has_more_moves() {
+            return;
+        }
+        let symbol = self.game.get_active_symbol();
+        let info = MinimaxPlayer::new(symbol)
+            .with_depth(HINT_DEPTH)
+            .search(&self.game);
+        if let Some(coords) = info.best_move {
+            self.cursor = coords;
+            self.message = format!(
+                "Hint: Row {}, Col {} (score {})",
+                coords.0, coords.1, info.score
+            );
+        }
+    }
+
+    /// Moves the game along when no human input is needed: a bot moves, or a player without moves passes.
+    pub fn step(&mut self) {
+        let active = self.game.active_player();
+        let symbol = self.game.get_active_symbol();
+        if !self.game.has_more_moves() {
+            return;
+        }
+        if !self.game.player_has_more_moves(active) {
+            self.game.change_active_player();
+            self.moves.push((symbol, None));
+            self.message = format!("{} has no moves and passes.", symbol);
+            return;
+        }
+
+        let before = self.game.board().clone();
+        let could_swap = self.game.can_swap();
+        self.game.play_turn();
+        let placed = placed(&before, self.game.board());
+        if placed.is_some() {
+            self.moves.push((symbol, placed));
+        } else if could_swap && self.game.active_player() != active {
+            self.message = format!("{} swaps and takes over the first move.", symbol);
+        }
+        if self.game.forfeited().is_some() {
+            self.message = format!("{} forfeits with an invalid move.", symbol);
+        }
+    }
+
+    /// Finds the board cell drawn at a 1-based screen column and line.
+    pub fn cell_at(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let (rows, cols) = (self.game.board().rows(), self.game.board().cols());
+        let line = y.checked_sub(1 + TOP)?;
+        let offset = x.checked_sub(1 + label_width(rows) + 2)?;
+        let (row, col) = (rows.checked_sub(line + 1)?, offset / 3);
+        if col < cols {
+            Some((row, col))
+        } else {
+            None
+        }
+    }
+
+    /// Draws the whole screen. Lines end in `\r\n` because the terminal is in raw mode.
+    pub fn render(&self) -> String {
+        let board = self.game.board();
+        let (rows, cols) = (board.rows(), board.cols());
+        let width = label_width(rows);
+        let waiting = self.needs_input() && self.game.has_more_moves();
+        let symbol = self.game.get_active_symbol();
+        let legal = if waiting {
+            self.game.successors(symbol)
+        } else {
+            vec![]
+        };
+        let flips = if waiting {
+            self.game.flips(self.cursor.0, self.cursor.1, symbol)
+        } else {
+            vec![]
+        };
+
+        let mut board_lines = vec![];
+        for row in (0..rows).rev() {
+            let mut line = format!("{:>width$} |", row, width = width);
+            for col in 0..cols {
+                let cell = match board.get_cell(row, col) {
+                    _ if board.is_blocked(row, col) => BLOCKED,
+                    Some(symbol) => symbol,
+                    None if legal.contains(&(row, col)) => '?',
+                    None => '.',
+                };
+                let style = if flips.contains(&(row, col)) {
+                    FLIP
+                } else if legal.contains(&(row, col)) {
+                    LEGAL
+                } else {
+                    ""
+                };
+                if (row, col) == self.cursor {
+                    line += &format!("{}[{}{}{}{}]{}", CURSOR, style, cell, RESET, CURSOR, RESET);
+                } else if style.is_empty() {
+                    line += &format!(" {} ", cell);
+                } else {
+                    line += &format!(" {}{}{} ", style, cell, RESET);
+                }
+            }
+            board_lines.push(line);
+        }
+        board_lines.push(format!(
+            "{:>width$}  {}",
+            "",
+            "---".repeat(cols),
+            width = width
+        ));
+        board_lines.push(format!(
+            "{:>width$}  {}",
+            "",
+            (0..cols)
+                .map(|col| format!("{:^3}", col))
+                .collect::<String>(),
+            width = width
+        ));
+
+        let panel = self.panel(rows + 2);
+        let mut screen = "Othello\r\n\r\n".to_string();
+        for (index, line) in board_lines.iter().enumerate() {
+            let padding = visible_width(line);
+            screen += line;
+            if let Some(text) = panel.get(index) {
+                screen += &" ".repeat((width + 3 + cols * 3 + 4).saturating_sub(padding));
+                screen += text;
+            }
+            screen += "\r\n";
+        }
+        screen += "\r\n";
+        screen += &self.status();
+        screen += "\r\n";
+        screen += "Arrows/wasd move, Enter plays, u undoes, h hints, q quits\r\n";
+        screen
+    }
+
+    /// The score panel and as much of the move list as fits in `lines` lines.
+    fn panel(&self, lines: usize) -> Vec<String> {
+        let counts = self.game.board().char_counts();
+        let mut panel = vec![];
+        for (number, player) in [(1, ActivePlayer::PlayerOne), (2, ActivePlayer::PlayerTwo)] {
+            let symbol = self.game.symbol_from_player(player);
+            let marker = if self.game.has_more_moves() && self.game.active_player() == player {
+                ">"
+            } else {
+                " "
+            };
+            panel.push(format!(
+                "{} Player {} ({}): {}",
+                marker,
+                number,
+                symbol,
+                counts.get(&symbol).unwrap_or(&0)
+            ));
+        }
+        panel.push(String::new());
+        panel.push("Moves:".to_string());
+
+        let room = lines.saturating_sub(panel.len());
+        let first = self.moves.len().saturating_sub(room);
+        for (index, (symbol, coords)) in self.moves.iter().enumerate().skip(first) {
+            panel.push(match coords {
+                Some((row, col)) => format!("{:>3}. {} {},{}", index + 1, symbol, row, col),
+                None => format!("{:>3}. {} pass", index + 1, symbol),
+            });
+        }
+        panel
+    }
+
+    fn status(&self) -> String {
+        if !self.game.has_more_moves() {
+            let result = self.game.get_winner().unwrap_or_default();
+            return format!("{} Press q to leave or u to take back a move.", result);
+        }
+        if !self.message.is_empty() {
+            return self.message.clone();
+        }
+        let symbol = self.game.get_active_symbol();
+        if self.needs_input() && self.game.can_swap() {
+            format!(
+                "Player {} ({}) to move, or p to swap.",
+                self.game.active_as_num(),
+                symbol
+            )
+        } else if self.needs_input() && !self.game.is_set_up() {
+            format!(
+                "Player {} ({}) to place a disc in the middle.",
+                self.game.active_as_num(),
+                symbol
+            )
+        } else if self.needs_input() {
+            format!("Player {} ({}) to move.", self.game.active_as_num(), symbol)
+        } else {
+            format!(
+                "Player {} ({}) is thinking...",
+                self.game.active_as_num(),
+                symbol
+            )
+        }
+    }
+}
+
+/// How many characters the row numbers take up.
+fn label_width(rows: usize) -> usize {
+    rows.saturating_sub(1).to_string().len()
+}
+
+/// The length of a line without its escape codes.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut escaped = false;
+    for c in line.chars() {
+        match (escaped, c) {
+            (false, '\x1b') => escaped = true,
+            (true, 'm') => escaped = false,
+            (true, _) => {}
+            (false, _) => width += 1,
+        }
+    }
+    width
+}
+
+/// The empty cell of `before` that holds a disc in `after`, if there is one.
+fn placed(before: &Board, after: &Board) -> Option<(usize, usize)> {
+    (0..before.rows())
+        .flat_map(|row| (0..before.cols()).map(move |col| (row, col)))
+        .find(|&(row, col)| before.is_cell_empty(row, col) && !after.is_cell_empty(row, col))
+}
+
+/// Puts the terminal in raw mode on the alternate screen, and puts it back when dropped.
+struct Terminal {
+    saved: String,
+}
+
+impl Terminal {
+    fn enter() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        // Alternate screen, hidden cursor, mouse clicks reported in SGR format
+        print!("\x1b[?1049h\x1b[?25l\x1b[?1000h\x1b[?1006h");
+        io::stdout().flush()?;
+        Ok(Self {
+            saved: saved.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[?1006l\x1b[?1000l\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Plays `game` to the end (or until the player quits) in the terminal interface and returns it.
+///
+/// `humans` says which players use the keyboard. Fails without touching the game if the terminal can't be switched
+/// to raw mode.
+pub fn run<'a>(game: Othello<'a>, humans: (bool, bool)) -> io::Result<Othello<'a>> {
+    let mut tui = Tui::new(game, humans);
+    let terminal = Terminal::enter()?;
+    let mut out = io::stdout();
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut buffer = [0; 64];
+
+    loop {
+        write!(out, "\x1b[H\x1b[2J{}", tui.render())?;
+        out.flush()?;
+        if !tui.needs_input() {
+            tui.step();
+            continue;
+        }
+
+        let read = input.read(&mut buffer)?;
+        if read == 0
+            || !parse_keys(&buffer[..read])
+                .into_iter()
+                .all(|key| tui.handle(key))
+        {
+            break;
+        }
+    }
+
+    drop(terminal);
+    Ok(tui.game)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn plays_and_takes_back_moves() {
+        let (one, two) = (
+            MinimaxPlayer::new('X'),
+            MinimaxPlayer::new('O').with_depth(1),
+        );
+        let game = Othello::with_players(&one, &two, 4, 4);
+        let mut tui = Tui::new(game, (true, false));
+        assert_eq!((2, 2), tui.cursor());
+
+        // (2, 2) is taken, so nothing happens
+        tui.handle(Key::Select);
+        assert!(tui.moves().is_empty());
+
+        tui.handle(Key::Down);
+        tui.handle(Key::Down);
+        assert_eq!((0, 2), tui.cursor());
+        assert!(tui.render().contains(&format!("{}O{}", FLIP, RESET)));
+        tui.handle(Key::Select);
+        assert_eq!(vec![('X', Some((0, 2)))], tui.moves());
+
+        // The bot replies on its own turn
+        assert!(!tui.needs_input());
+        tui.step();
+        assert_eq!(2, tui.moves().len());
+        assert!(tui.needs_input());
+
+        tui.handle(Key::Undo);
+        assert!(tui.moves().is_empty());
+        assert_eq!(2, tui.game().board().char_counts()[&'X']);
+    }
+
+    #[test]
+    fn clicks_land_on_cells() {
+        let (one, two) = (MinimaxPlayer::new('X'), MinimaxPlayer::new('O'));
+        let tui = Tui::new(Othello::with_players(&one, &two, 4, 4), (true, true));
+        let screen = tui.render();
+        let lines = screen.split("\r\n").collect::<Vec<_>>();
+        // Row 3 is drawn first, right after the title
+        assert!(lines[TOP].starts_with("3 |"));
+        assert_eq!(Some((3, 0)), tui.cell_at(5, TOP + 1));
+        assert_eq!(Some((0, 3)), tui.cell_at(14, TOP + 4));
+        assert_eq!(None, tui.cell_at(1, TOP + 1));
+        assert_eq!(None, tui.cell_at(5, TOP + 5));
+    }
+}